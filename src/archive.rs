@@ -0,0 +1,50 @@
+//! Reading and writing of the zip archives community packs are distributed as, with the `zip`
+//! crate.
+
+use anyhow::{bail, Context, Result};
+use std::io::{Cursor, Read, Write};
+use std::path::{Component, Path};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+/// A file inside an archive, with its path inside the archive.
+pub struct Entry {
+    pub name: String,
+    pub content: Vec<u8>,
+}
+
+/// Reads the files of an archive, in the order of its central directory. Directories are left
+/// out. An entry whose path would lead outside of the directory the archive is extracted to,
+/// with `..` or from the root, is refused.
+pub fn read(archive: &[u8]) -> Result<Vec<Entry>> {
+    let mut archive = ZipArchive::new(Cursor::new(archive)).context("Not a zip archive")?;
+    let mut entries = Vec::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.name()?.into_owned();
+        let is_relative = Path::new(&name)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !is_relative || file.enclosed_name().is_none() {
+            bail!("The entry {name} of the archive points outside of it");
+        }
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)
+            .with_context(|| format!("Could not read the entry {name} of the archive"))?;
+        entries.push(Entry { name, content });
+    }
+    Ok(entries)
+}
+
+/// Writes the entries into a new archive, deflating them.
+pub fn write(entries: &[Entry]) -> Result<Vec<u8>> {
+    let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for entry in entries {
+        archive.start_file(&*entry.name, options)?;
+        archive.write_all(&entry.content)?;
+    }
+    Ok(archive.finish()?.into_inner())
+}
@@ -0,0 +1,183 @@
+//! A human readable list of what the conversion changed, for reviewing converted files. It is
+//! built from the conversion report and a comparison of the source with the converted file.
+
+use crate::{ConversionReport, EnemyField, FieldStatus};
+use json::JsonValue;
+use std::fmt;
+use std::str::FromStr;
+
+/// A change made by the conversion. Paths are dotted, like `EnemiesNoSync.ED_Spider_Grunt`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Change {
+    /// A field that was moved, possibly renamed, keeping its value.
+    Moved { from: String, to: String },
+    /// A field that was replaced by another one with a different value, like StartingNitra.
+    Replaced {
+        from: String,
+        to: String,
+        value: JsonValue,
+    },
+    /// A resistance that became a CD2 damage multiplier.
+    Inverted {
+        from: String,
+        to: String,
+        resistance: JsonValue,
+        multiplier: JsonValue,
+    },
+    /// A field that the source file doesn't have and the conversion added.
+    Added { field: String, value: JsonValue },
+    /// A field that was left out of the converted file.
+    Dropped { field: String, reason: &'static str },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Moved { from, to } => write!(f, "~ {from} -> {to}"),
+            Change::Replaced { from, to, value } => write!(f, "~ {from} -> {to} = {value}"),
+            Change::Inverted {
+                from,
+                to,
+                resistance,
+                multiplier,
+            } => write!(f, "~ {from} = {resistance} -> {to} = {multiplier}"),
+            Change::Added { field, value } => write!(f, "+ {field} = {value}"),
+            Change::Dropped { field, reason } => write!(f, "- {field} ({reason})"),
+        }
+    }
+}
+
+fn enemy_path(EnemyField { enemy, field }: &EnemyField, pawn_stat: bool) -> String {
+    if pawn_stat {
+        format!("EnemyDescriptors.{enemy}.PawnStats.{field}")
+    } else {
+        format!("EnemyDescriptors.{enemy}.{field}")
+    }
+}
+
+/// Lists the changes made when converting `cd1_json` to `cd2_json`, in the order of the source
+/// file, followed by the fields that were dropped.
+pub(crate) fn changelog(
+    cd1_json: &JsonValue,
+    cd2_json: &JsonValue,
+    report: &ConversionReport,
+    translation_data: &JsonValue,
+) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let pool_renames = &translation_data["POOL_RENAMES"];
+    for (key, _) in cd1_json.entries() {
+        let status = translation_data["TOP_MODULES"][key]
+            .as_str()
+            .map(FieldStatus::from_str);
+        let Some(Ok(FieldStatus::Valid(module))) = status else {
+            continue;
+        };
+        let field = match pool_renames[key]["name"].as_str() {
+            Some(new_name) if cd2_json[module.as_str()].has_key(new_name) => new_name,
+            _ => key,
+        };
+        if cd2_json[module.as_str()].has_key(field) {
+            changes.push(Change::Moved {
+                from: key.to_string(),
+                to: format!("{module}.{field}"),
+            });
+        }
+    }
+    if cd1_json["BaseHazard"].is_null() && cd2_json["DifficultySetting"].has_key("BaseHazard") {
+        changes.push(Change::Added {
+            field: "DifficultySetting.BaseHazard".to_string(),
+            value: cd2_json["DifficultySetting"]["BaseHazard"].clone(),
+        });
+    }
+    let cost = &cd2_json["Resupply"]["Cost"];
+    if cost["Mutate"].is_string() {
+        changes.push(Change::Replaced {
+            from: "StartingNitra, ResupplyCost".to_string(),
+            to: "Resupply.Cost".to_string(),
+            value: cost.clone(),
+        });
+    } else if cd1_json.has_key("ResupplyCost") {
+        changes.push(Change::Moved {
+            from: "ResupplyCost".to_string(),
+            to: "Resupply.Cost".to_string(),
+        });
+    } else if !cost.is_null() {
+        changes.push(Change::Added {
+            field: "Resupply.Cost".to_string(),
+            value: cost.clone(),
+        });
+    }
+    if cd2_json.has_key("EnemiesNoSync") {
+        changes.push(Change::Moved {
+            from: "EnemyDescriptors".to_string(),
+            to: "EnemiesNoSync".to_string(),
+        });
+    }
+    for remapped in &report.remapped_pawn_stats {
+        let from = format!(
+            "EnemyDescriptors.{}.PawnStats.{}",
+            remapped.enemy, remapped.stat
+        );
+        let to = format!("EnemiesNoSync.{}.{}", remapped.enemy, remapped.target);
+        let resistance = &cd1_json["EnemyDescriptors"][remapped.enemy.as_str()]["PawnStats"]
+            [remapped.stat.as_str()];
+        let multiplier = match remapped.target.split_once('.') {
+            Some((module, field)) => {
+                &cd2_json["EnemiesNoSync"][remapped.enemy.as_str()][module][field]
+            }
+            None => &cd2_json["EnemiesNoSync"][remapped.enemy.as_str()][remapped.target.as_str()],
+        };
+        if resistance == multiplier {
+            changes.push(Change::Moved { from, to });
+        } else {
+            changes.push(Change::Inverted {
+                from,
+                to,
+                resistance: resistance.clone(),
+                multiplier: multiplier.clone(),
+            });
+        }
+    }
+    for elite in &report.elite_base_overrides {
+        changes.push(Change::Added {
+            field: format!("EnemiesNoSync.{}.ForceEliteBase", elite.enemy),
+            value: elite.enemy.as_str().into(),
+        });
+    }
+    let dropped = |field: String, reason| Change::Dropped { field, reason };
+    changes.extend(
+        report
+            .deprecated_fields
+            .iter()
+            .map(|field| dropped(field.clone(), "deprecated")),
+    );
+    changes.extend(
+        report
+            .deprecated_enemy_controls
+            .iter()
+            .map(|control| dropped(enemy_path(control, false), "deprecated")),
+    );
+    changes.extend(report.overwritten_pawn_stats.iter().map(|overwritten| {
+        dropped(
+            format!(
+                "EnemyDescriptors.{}.PawnStats.{}",
+                overwritten.enemy, overwritten.stat
+            ),
+            "overwritten",
+        )
+    }));
+    changes.extend(
+        report
+            .unsupported_fields
+            .iter()
+            .map(|field| dropped(field.clone(), "unsupported")),
+    );
+    changes.extend(
+        report
+            .unsupported_pawn_stats
+            .iter()
+            .map(|stat| dropped(enemy_path(stat, true), "unsupported")),
+    );
+    changes
+}
@@ -0,0 +1,224 @@
+//! The `//` and `/* */` comments of hand-edited difficulty files, which are removed before
+//! parsing, kept to be written back into the converted file. Only whole-line comments are kept:
+//! the ones at the top and bottom of the file, before a top-level field and before an enemy.
+//! The converted file moves fields around, so each comment goes before the field it was about,
+//! wherever it ended up.
+
+use tracing::{event, Level};
+
+/// The comment lines of a file, by what they were written before.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Comments {
+    /// Before the opening brace of the file.
+    header: Vec<String>,
+    /// Before a top-level field, by key.
+    fields: Vec<(String, Vec<String>)>,
+    /// Before an enemy of EnemyDescriptors or EnemiesNoSync, by enemy.
+    enemies: Vec<(String, Vec<String>)>,
+    /// After the last field of the file.
+    footer: Vec<String>,
+}
+
+/// Top-level fields holding the enemies, in CD1 and in CD2.
+const ENEMY_MODULES: [&str; 2] = ["EnemyDescriptors", "EnemiesNoSync"];
+
+/// State of the scan of a file, carried from one line to the next.
+#[derive(Default)]
+pub(crate) struct Scan {
+    pub(crate) depth: usize,
+    pub(crate) in_string: bool,
+    in_block_comment: bool,
+}
+
+impl Scan {
+    /// Walks a line, keeping track of strings, comments and of the nesting depth. Returns the
+    /// position of the quote closing the string the line started in, if any.
+    pub(crate) fn line(&mut self, line: &str) -> Option<usize> {
+        let starts_in_string = self.in_string;
+        let mut closed_at = None;
+        let mut escaped = false;
+        let mut chars = line.char_indices().peekable();
+        while let Some((pos, ch)) = chars.next() {
+            if self.in_block_comment {
+                if ch == '*' && chars.next_if(|&(_, next)| next == '/').is_some() {
+                    self.in_block_comment = false;
+                }
+            } else if self.in_string {
+                if escaped {
+                    escaped = false;
+                } else if ch == '\\' {
+                    escaped = true;
+                } else if ch == '"' {
+                    self.in_string = false;
+                    if starts_in_string && closed_at.is_none() {
+                        closed_at = Some(pos);
+                    }
+                }
+            } else {
+                match (ch, chars.peek().map(|&(_, next)| next)) {
+                    ('/', Some('/')) => break,
+                    ('/', Some('*')) => {
+                        chars.next();
+                        self.in_block_comment = true;
+                    }
+                    ('"', _) => self.in_string = true,
+                    ('{' | '[', _) => self.depth += 1,
+                    ('}' | ']', _) => self.depth = self.depth.saturating_sub(1),
+                    _ => (),
+                }
+            }
+        }
+        closed_at
+    }
+}
+
+/// Returns the key of a line like `"Key": value`, if it has that shape.
+fn line_key(line: &str) -> Option<&str> {
+    let rest = line.trim_start().strip_prefix('"')?;
+    let mut escaped = false;
+    let key_end = rest.char_indices().find_map(|(pos, ch)| match ch {
+        _ if escaped => {
+            escaped = false;
+            None
+        }
+        '\\' => {
+            escaped = true;
+            None
+        }
+        '"' => Some(pos),
+        _ => None,
+    })?;
+    rest[key_end + 1..]
+        .trim_start()
+        .starts_with(':')
+        .then_some(&rest[..key_end])
+}
+
+/// Collects the whole-line comments of a file with what they were written before.
+pub(crate) fn extract(file_str: &str) -> Comments {
+    let mut comments = Comments::default();
+    let mut pending: Vec<String> = Vec::new();
+    let mut scan = Scan::default();
+    let mut top_level_key = String::new();
+    let mut dropped = 0;
+    for line in file_str.lines() {
+        let trimmed = line.trim();
+        if scan.in_block_comment {
+            pending.push(trimmed.to_string());
+            scan.line(line);
+            continue;
+        }
+        if !scan.in_string && (trimmed.starts_with("//") || trimmed.starts_with("/*")) {
+            pending.push(trimmed.to_string());
+            scan.line(line);
+            continue;
+        }
+        if !pending.is_empty() && !scan.in_string {
+            let comment = std::mem::take(&mut pending);
+            match (scan.depth, line_key(line)) {
+                (0, _) => comments.header.extend(comment),
+                (1, Some(key)) => comments.fields.push((key.to_string(), comment)),
+                (2, Some(enemy)) if ENEMY_MODULES.contains(&top_level_key.as_str()) => {
+                    comments.enemies.push((enemy.to_string(), comment));
+                }
+                (1, None) if trimmed.starts_with('}') => comments.footer.extend(comment),
+                _ => dropped += comment.len(),
+            }
+        }
+        if scan.depth == 1 && !scan.in_string {
+            if let Some(key) = line_key(line) {
+                top_level_key = key.to_string();
+            }
+        }
+        scan.line(line);
+    }
+    comments.footer.extend(pending);
+    if dropped > 0 {
+        event!(
+            Level::INFO,
+            "Only the comments before top-level fields and enemies are kept, {dropped} comment lines are dropped."
+        );
+    }
+    comments
+}
+
+/// Comments waiting for the line of their field, taken out once placed.
+type Unplaced<'a> = Vec<Option<&'a (String, Vec<String>)>>;
+
+/// Takes out the first unplaced comment whose key matches.
+fn take<'a>(comments: &mut Unplaced<'a>, matches: impl Fn(&str) -> bool) -> Option<&'a [String]> {
+    comments
+        .iter_mut()
+        .find(|comment| comment.is_some_and(|(key, _)| matches(key)))
+        .and_then(Option::take)
+        .map(|(_, lines)| lines.as_slice())
+}
+
+impl Comments {
+    fn is_empty(&self) -> bool {
+        self.header.is_empty()
+            && self.fields.is_empty()
+            && self.enemies.is_empty()
+            && self.footer.is_empty()
+    }
+
+    /// Writes the comments back into a pretty-printed converted file, each one before the line
+    /// of the field it was about, with the same indentation. Top-level fields are looked for at
+    /// the top level and inside the modules they may have been moved to. The comments whose
+    /// field is gone go after the header.
+    pub(crate) fn reattach(&self, json_string: &str, line_ending: &str) -> String {
+        if self.is_empty() {
+            return json_string.to_string();
+        }
+        let mut fields: Unplaced = self.fields.iter().map(Some).collect();
+        let mut enemies: Unplaced = self.enemies.iter().map(Some).collect();
+        let mut body = Vec::new();
+        let mut scan = Scan::default();
+        let mut top_level_key = String::new();
+        for line in json_string.lines() {
+            let key = line_key(line).filter(|_| !scan.in_string);
+            let comment = match (scan.depth, key) {
+                (1, Some(key)) => {
+                    top_level_key = key.to_string();
+                    take(&mut fields, |field| match field {
+                        "EnemyDescriptors" => key == "EnemiesNoSync",
+                        "StartingNitra" | "ResupplyCost" => key == "Resupply",
+                        _ => field == key,
+                    })
+                }
+                (2, Some(key)) if ENEMY_MODULES.contains(&top_level_key.as_str()) => {
+                    take(&mut enemies, |enemy| enemy == key)
+                }
+                (2, Some(key)) => take(&mut fields, |field| field == key),
+                _ => None,
+            };
+            if let Some(comment) = comment {
+                let indentation = &line[..line.len() - line.trim_start().len()];
+                body.extend(comment.iter().map(|text| format!("{indentation}{text}")));
+            }
+            body.push(line.to_string());
+            scan.line(line);
+        }
+        let unplaced: Vec<&String> = fields
+            .iter()
+            .chain(&enemies)
+            .flatten()
+            .flat_map(|(_, lines)| lines)
+            .collect();
+        if !unplaced.is_empty() {
+            event!(
+                Level::INFO,
+                "{} comment lines are about fields that are gone, moving them to the top of the file.",
+                unplaced.len()
+            );
+        }
+        self.header
+            .iter()
+            .chain(unplaced)
+            .cloned()
+            .chain(body)
+            .chain(self.footer.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(line_ending)
+    }
+}
@@ -0,0 +1,105 @@
+//! Structural comparison of two JSON documents, for checking a fresh conversion against a
+//! previously converted file. The order of the keys of objects doesn't matter, the order of the
+//! members of arrays does.
+
+use json::JsonValue;
+use std::fmt;
+
+/// A difference between a reference document and another one. Paths are dotted, array members
+/// being given by their index, like `Pools.EnemyPool.0`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Difference {
+    /// A value that only the other document has.
+    Added { path: String, value: JsonValue },
+    /// A value that only the reference document has.
+    Removed { path: String, value: JsonValue },
+    /// A value that is different in the other document.
+    Changed {
+        path: String,
+        reference: JsonValue,
+        value: JsonValue,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difference::Added { path, value } => write!(f, "+ {path} = {value}"),
+            Difference::Removed { path, value } => write!(f, "- {path} = {value}"),
+            Difference::Changed {
+                path,
+                reference,
+                value,
+            } => write!(f, "~ {path} = {reference} -> {value}"),
+        }
+    }
+}
+
+/// Lists what `value` has different from `reference`, in the order of the reference, followed by
+/// what only `value` has.
+pub fn diff(reference: &JsonValue, value: &JsonValue) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    diff_at(reference, value, String::new(), &mut differences);
+    differences
+}
+
+fn diff_at(
+    reference: &JsonValue,
+    value: &JsonValue,
+    path: String,
+    differences: &mut Vec<Difference>,
+) {
+    let child_path = |key: &dyn fmt::Display| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (reference, value) {
+        (JsonValue::Object(_), JsonValue::Object(_)) => {
+            for (key, member) in reference.entries() {
+                if value.has_key(key) {
+                    diff_at(member, &value[key], child_path(&key), differences);
+                } else {
+                    differences.push(Difference::Removed {
+                        path: child_path(&key),
+                        value: member.clone(),
+                    });
+                }
+            }
+            for (key, member) in value.entries() {
+                if !reference.has_key(key) {
+                    differences.push(Difference::Added {
+                        path: child_path(&key),
+                        value: member.clone(),
+                    });
+                }
+            }
+        }
+        (JsonValue::Array(references), JsonValue::Array(members)) => {
+            for (index, (expected, member)) in references.iter().zip(members).enumerate() {
+                diff_at(expected, member, child_path(&index), differences);
+            }
+            for (index, member) in references.iter().enumerate().skip(members.len()) {
+                differences.push(Difference::Removed {
+                    path: child_path(&index),
+                    value: member.clone(),
+                });
+            }
+            for (index, member) in members.iter().enumerate().skip(references.len()) {
+                differences.push(Difference::Added {
+                    path: child_path(&index),
+                    value: member.clone(),
+                });
+            }
+        }
+        _ if reference != value => differences.push(Difference::Changed {
+            path,
+            reference: reference.clone(),
+            value: value.clone(),
+        }),
+        _ => (),
+    }
+}
@@ -0,0 +1,100 @@
+//! The json crate keeps the last value of a duplicated key without saying anything, which after
+//! a bad merge makes for confusing conversions. Duplicated keys are found with a light scan of
+//! the text before parsing.
+
+use std::collections::HashSet;
+use tracing::{event, Level};
+
+enum Frame {
+    Object {
+        /// Path of the object, like `EnemyDescriptors.ED_Spider_Grunt`.
+        path: String,
+        keys: HashSet<String>,
+        expecting_key: bool,
+    },
+    /// Arrays give their path to the objects inside them.
+    Array { path: String },
+}
+
+/// Returns the path of every duplicated key, in the order they are found. Top-level keys are
+/// given by name, nested ones by their path, like `EnemyDescriptors.ED_Spider_Grunt`.
+pub(crate) fn find_duplicate_keys(text: &str) -> Vec<String> {
+    let mut duplicates = Vec::new();
+    let mut frames: Vec<Frame> = Vec::new();
+    // The last key read, which names the object or array that may follow it:
+    let mut last_key = String::new();
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' => {
+                let mut string = String::new();
+                let mut escaped = false;
+                for ch in chars.by_ref() {
+                    if escaped {
+                        escaped = false;
+                    } else if ch == '\\' {
+                        escaped = true;
+                    } else if ch == '"' {
+                        break;
+                    }
+                    string.push(ch);
+                }
+                if let Some(Frame::Object {
+                    path,
+                    keys,
+                    expecting_key: expecting_key @ true,
+                }) = frames.last_mut()
+                {
+                    *expecting_key = false;
+                    if !keys.insert(string.clone()) {
+                        duplicates.push(if path.is_empty() {
+                            string.clone()
+                        } else {
+                            format!("{path}.{string}")
+                        });
+                    }
+                    last_key = string;
+                }
+            }
+            '{' | '[' => {
+                let path = match frames.last() {
+                    Some(Frame::Object { path, .. }) if !path.is_empty() => {
+                        format!("{path}.{last_key}")
+                    }
+                    Some(Frame::Object { .. }) => last_key.clone(),
+                    Some(Frame::Array { path }) => path.clone(),
+                    None => String::new(),
+                };
+                frames.push(if ch == '{' {
+                    Frame::Object {
+                        path,
+                        keys: HashSet::new(),
+                        expecting_key: true,
+                    }
+                } else {
+                    Frame::Array { path }
+                });
+            }
+            '}' | ']' => {
+                frames.pop();
+            }
+            ',' => {
+                if let Some(Frame::Object { expecting_key, .. }) = frames.last_mut() {
+                    *expecting_key = true;
+                }
+            }
+            _ => (),
+        }
+    }
+    duplicates
+}
+
+/// Warns about every duplicated key in the text, see [`find_duplicate_keys`].
+pub(crate) fn warn_duplicate_keys(text: &str) {
+    for key in find_duplicate_keys(text) {
+        event!(
+            Level::WARN,
+            "Duplicated key: [{key}]. Only its last value is kept."
+        );
+    }
+}
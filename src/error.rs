@@ -0,0 +1,88 @@
+use std::{error, fmt, io};
+
+/// Result of the conversion functions of this crate.
+pub type Result<T, E = ConversionError> = std::result::Result<T, E>;
+
+/// Everything that can make a conversion fail, so that callers can tell the cases apart.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConversionError {
+    /// A file couldn't be read. The conversion itself works on text, this is for callers
+    /// reading the files themselves.
+    Io(io::Error),
+    /// The input is not a proper JSON, even after removing comments and trailing commas.
+    Parse(json::Error),
+    /// The translation data lacks some of the keys the conversion relies on.
+    MissingTranslationData(Vec<String>),
+    /// The translation data has all its keys but some of their values are wrong, like a pawn
+    /// stat going to an unknown CD2 module.
+    InvalidTranslationData(String),
+    /// The input was rejected before parsing it, like when it is over the size or depth limits.
+    InvalidSource(String),
+    /// An override of [`Options::overrides`](crate::Options::overrides) goes through a value
+    /// that is neither an object nor an array, or past the end of an array.
+    InvalidOverride(String),
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionError::Io(_) => write!(f, "Something went wrong when reading the file"),
+            ConversionError::Parse(json::Error::UnexpectedCharacter { ch, line, column }) => {
+                write!(
+                    f,
+                    "The JSON parser found an unexpected character [{ch}] at line {line}, \
+                    column {column}. Is it a proper JSON?"
+                )
+            }
+            ConversionError::Parse(json::Error::UnexpectedEndOfJson) => write!(
+                f,
+                "The file ended before the JSON was complete. Is a closing bracket or quote \
+                missing?"
+            ),
+            ConversionError::Parse(_) => write!(
+                f,
+                "The JSON parser couldn't parse the file. Is it a proper JSON?"
+            ),
+            ConversionError::MissingTranslationData(keys) => write!(
+                f,
+                "The translation data is missing the following keys: {}",
+                keys.join(", ")
+            ),
+            ConversionError::InvalidTranslationData(reason)
+            | ConversionError::InvalidSource(reason)
+            | ConversionError::InvalidOverride(reason) => {
+                write!(f, "{reason}")
+            }
+        }
+    }
+}
+
+impl error::Error for ConversionError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ConversionError::Io(e) => Some(e),
+            // The position of the unexpected characters is already in the message:
+            ConversionError::Parse(
+                json::Error::UnexpectedCharacter { .. } | json::Error::UnexpectedEndOfJson,
+            ) => None,
+            ConversionError::Parse(e) => Some(e),
+            ConversionError::MissingTranslationData(_)
+            | ConversionError::InvalidTranslationData(_)
+            | ConversionError::InvalidSource(_)
+            | ConversionError::InvalidOverride(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConversionError {
+    fn from(e: io::Error) -> Self {
+        ConversionError::Io(e)
+    }
+}
+
+impl From<json::Error> for ConversionError {
+    fn from(e: json::Error) -> Self {
+        ConversionError::Parse(e)
+    }
+}
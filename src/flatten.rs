@@ -0,0 +1,63 @@
+//! Collapsing of the single entry arrays of a converted file into the plain values CD2 prefers,
+//! with `flatten`. It is lossy for authors who meant the array form, hence opt-in.
+
+use crate::is_weighted_bins;
+use json::JsonValue;
+
+/// Modules whose fields are numbers, per-player arrays or weighted bins.
+const NUMERIC_MODULES: [&str; 3] = ["DifficultySetting", "Caps", "Resupply"];
+
+/// The plain value a single entry array stands for, if it is unambiguous: a pool with a single
+/// enemy is that enemy, a per-player array with a single value is that value and weighted bins
+/// with a single bin of equal bounds are that bound. Err with the reason for the single entry
+/// arrays that can't be flattened without guessing, Ok(None) for the values to leave alone.
+fn flattened(value: &JsonValue, pool: bool) -> Result<Option<JsonValue>, &'static str> {
+    if !value.is_array() || value.len() != 1 {
+        return Ok(None);
+    }
+    let entry = &value[0];
+    if pool {
+        return match entry.as_str() {
+            Some(_) => Ok(Some(entry.clone())),
+            None => Err("its entry is not an enemy name"),
+        };
+    }
+    if entry.is_number() {
+        Ok(Some(entry.clone()))
+    } else if is_weighted_bins(value) {
+        if entry["min"].is_number() && entry["min"] == entry["max"] {
+            Ok(Some(entry["min"].clone()))
+        } else {
+            Err("its bin is a range rather than a single value")
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+/// Flattens the single entry arrays of the pools, of the numeric modules and of the enemy
+/// controls, in place. Returns a description of each array that was left as it is because
+/// flattening it would be ambiguous.
+pub(crate) fn flatten(cd2_json: &mut JsonValue) -> Vec<String> {
+    let mut ambiguous = Vec::new();
+    let mut flatten_fields = |fields: &mut JsonValue, path: &str, pool: bool| {
+        for (field, value) in fields.entries_mut() {
+            match flattened(value, pool) {
+                Ok(Some(flat)) => *value = flat,
+                Ok(None) => (),
+                Err(reason) => ambiguous.push(format!("[{path}.{field}] is kept, {reason}")),
+            }
+        }
+    };
+    // Only the modules already there are looked at, indexing would add the others:
+    for (module, fields) in cd2_json.entries_mut() {
+        if NUMERIC_MODULES.contains(&module) || module == "Pools" {
+            flatten_fields(fields, module, module == "Pools");
+        } else if module == "EnemiesNoSync" {
+            for (enemy, controls) in fields.entries_mut() {
+                flatten_fields(controls, &format!("EnemiesNoSync.{enemy}"), false);
+            }
+        }
+    }
+    ambiguous
+}
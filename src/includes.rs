@@ -0,0 +1,147 @@
+//! Resolution of the `"$include": "path.json"` directives some authors split their CD1 files
+//! with, see [`resolve_includes_str`](crate::resolve_includes_str). The included files are read
+//! through a loader given by the caller, so that the crate itself doesn't touch the filesystem.
+
+use crate::{parse_json_str_with_multilines, ConversionError, Result};
+use json::JsonValue;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Key of the directive, whose value is the path of the file to include, or a list of them.
+pub(crate) const INCLUDE_KEY: &str = "$include";
+/// Maximum number of nested includes, past which a file is assumed to be wrong.
+pub const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Resolves the includes of CD1 documents, keeping track of the files being included to detect
+/// cycles.
+pub(crate) struct Resolver<'a> {
+    load: &'a mut dyn FnMut(&Path) -> io::Result<String>,
+    /// Files whose includes are being resolved, the outermost first.
+    stack: Vec<PathBuf>,
+}
+
+impl<'a> Resolver<'a> {
+    pub(crate) fn new(source: &Path, load: &'a mut dyn FnMut(&Path) -> io::Result<String>) -> Self {
+        Resolver {
+            load,
+            stack: vec![normalized(source)],
+        }
+    }
+
+    /// Replaces the directives of every object of `value` by the objects of the files they name.
+    /// The keys of an object take precedence over the included ones, and a later file over an
+    /// earlier one. Objects found under the same key on both sides are merged the same way.
+    pub(crate) fn resolve(&mut self, value: &mut JsonValue) -> Result<()> {
+        match value {
+            JsonValue::Object(_) => {
+                for (_, member) in value.entries_mut() {
+                    self.resolve(member)?;
+                }
+                let directive = value.remove(INCLUDE_KEY);
+                if directive.is_null() {
+                    return Ok(());
+                }
+                let paths: Vec<&str> = match directive.as_str() {
+                    Some(path) => vec![path],
+                    None => directive.members().filter_map(JsonValue::as_str).collect(),
+                };
+                if paths.is_empty() || (directive.is_array() && paths.len() != directive.len()) {
+                    return Err(self.error(format!(
+                        "The {INCLUDE_KEY} [{directive}] is not a path nor a list of paths"
+                    )));
+                }
+                let mut merged = JsonValue::new_object();
+                for path in paths {
+                    let included = self.include(path)?;
+                    merge(&mut merged, included);
+                }
+                merge(&mut merged, value.take());
+                *value = merged;
+            }
+            JsonValue::Array(members) => {
+                for member in members {
+                    self.resolve(member)?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Reads, parses and resolves a file included by the innermost file being resolved.
+    fn include(&mut self, path: &str) -> Result<JsonValue> {
+        let including = self
+            .stack
+            .last()
+            .expect("the source is always on the stack");
+        let dir = including.parent().unwrap_or(Path::new(""));
+        let path = normalized(&dir.join(path));
+        if self.stack.contains(&path) {
+            return Err(self.error(format!(
+                "Including [{}] again makes a cycle",
+                path.display()
+            )));
+        }
+        if self.stack.len() > MAX_INCLUDE_DEPTH {
+            return Err(self.error(format!(
+                "Includes are nested deeper than the limit of {MAX_INCLUDE_DEPTH} levels"
+            )));
+        }
+        let input = (self.load)(&path).map_err(|e| {
+            self.error(format!(
+                "The included file [{}] couldn't be read: {e}",
+                path.display()
+            ))
+        })?;
+        let (mut included, multilines) = parse_json_str_with_multilines(&input)?;
+        multilines.append_to(&mut included);
+        if !included.is_object() {
+            return Err(self.error(format!(
+                "The included file [{}] is not a JSON object",
+                path.display()
+            )));
+        }
+        self.stack.push(path);
+        self.resolve(&mut included)?;
+        self.stack.pop();
+        Ok(included)
+    }
+
+    /// An error about the innermost file being resolved.
+    fn error(&self, reason: String) -> ConversionError {
+        let including = self
+            .stack
+            .last()
+            .expect("the source is always on the stack");
+        ConversionError::InvalidSource(format!("{reason}, in [{}]", including.display()))
+    }
+}
+
+/// Merges the object `from` into the object `into`, the keys of `from` taking precedence except
+/// for the objects on both sides, which are merged.
+fn merge(into: &mut JsonValue, mut from: JsonValue) {
+    for (key, value) in from.entries_mut() {
+        let value = value.take();
+        if into[key].is_object() && value.is_object() {
+            merge(&mut into[key], value);
+        } else {
+            into[key] = value;
+        }
+    }
+}
+
+/// The path with its `.` and `..` components resolved, without touching the filesystem, so that
+/// the same file included through different paths is recognized.
+fn normalized(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
@@ -0,0 +1,70 @@
+//! Hand-edited difficulty files often have `//` or `/* */` comments and trailing commas, which
+//! are not proper JSON. They are removed before parsing, leaving strings untouched.
+
+use std::borrow::Cow;
+use tracing::{event, Level};
+
+/// Removes the comments and the trailing commas found outside of strings. Removed characters
+/// are replaced by spaces and newlines are kept, so the parser still reports the original line
+/// and column of any error.
+pub(crate) fn strip_comments_and_trailing_commas(text: &str) -> Cow<'_, str> {
+    let mut stripped = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut in_string = false;
+    let mut escaped = false;
+    // Position in `stripped` of the last comma, as long as only whitespace and comments follow:
+    let mut pending_comma = None;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                in_string = false;
+            }
+            stripped.push(ch);
+            continue;
+        }
+        match (ch, chars.peek()) {
+            ('/', Some('/')) => {
+                changed = true;
+                while chars.next_if(|&next| next != '\n').is_some() {}
+            }
+            ('/', Some('*')) => {
+                changed = true;
+                chars.next();
+                stripped.push_str("  ");
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        stripped.push(' ');
+                        break;
+                    }
+                    stripped.push(if next == '\n' { '\n' } else { ' ' });
+                    previous = next;
+                }
+            }
+            _ if ch.is_whitespace() => stripped.push(ch),
+            _ => {
+                if let (Some(pos), '}' | ']') = (pending_comma, ch) {
+                    changed = true;
+                    stripped.replace_range(pos..pos + 1, " ");
+                }
+                pending_comma = (ch == ',').then_some(stripped.len());
+                in_string = ch == '"';
+                stripped.push(ch);
+            }
+        }
+    }
+    if changed {
+        event!(
+            Level::INFO,
+            "Comments or trailing commas found in the file. Removing them before parsing."
+        );
+        Cow::Owned(stripped)
+    } else {
+        Cow::Borrowed(text)
+    }
+}
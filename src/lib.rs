@@ -0,0 +1,1795 @@
+//! Conversion of Custom Difficulty 1 (CD1) files to the CD2 format.
+//!
+//! [`convert`] translates an already parsed CD1 document. [`convert_str`] and
+//! [`convert_str_with`] work on the text of the file instead, taking care of the multiline
+//! fields commonly found in difficulty files, which are not proper JSON. [`reverse_convert`] and
+//! [`reverse_convert_str_with`] go the other way, from CD2 back to CD1, and [`reformat_str`]
+//! only rewrites a CD2 file in the standard layout. [`diff_str`] compares a converted file with
+//! a previous version of it and [`resolve_includes_str`] merges the files a CD1 file includes
+//! before converting it, the reverse of [`split_str`]. The stages of the conversion, like
+//! [`convert_enemies`], can also be run on their own for migrating a file a part at a time.
+
+use json::{object, JsonValue};
+use std::fmt;
+use std::io;
+use std::mem;
+use std::path::Path;
+use std::str::FromStr;
+use tracing::{event, span, Level};
+
+mod changelog;
+mod comments;
+mod diff;
+mod duplicates;
+mod error;
+mod flatten;
+mod includes;
+mod lenient;
+mod multilines;
+mod pointer;
+mod report;
+mod reverse;
+mod schema;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use changelog::Change;
+use comments::Comments;
+pub use diff::{diff, Difference};
+pub use error::{ConversionError, Result};
+pub use includes::MAX_INCLUDE_DEPTH;
+use multilines::Multilines;
+pub use pointer::Override;
+pub use report::{ConversionReport, EliteOverride, EnemyField, RemappedPawnStat, UnknownBase};
+pub use reverse::reverse_convert;
+
+/// CD1 to CD2 translation data, embedded at compile time.
+pub const TRANSLATION_DATA: &str = include_str!("cd2-modules.json");
+/// Top-level keys of the translation data that the conversion relies on.
+const TRANSLATION_DATA_KEYS: [&str; 10] = [
+    "TOP_MODULES",
+    "ESCORT_MULE",
+    "PAWN_STATS",
+    "VALID_ENEMY_CONTROLS",
+    "VANILLA_ELITE_ENEMIES",
+    "KNOWN_BASE_ENEMIES",
+    "POOL_RENAMES",
+    "ENEMY_CONTROL_ALIASES",
+    "BASE_HAZARD_DEFAULT",
+    // Required even without the sanity check, which would otherwise silently check nothing:
+    "SANITY_BOUNDS",
+];
+/// Order of the top-level keys in the converted file. Keys missing from this list go after,
+/// in the order they were created.
+const TOP_LEVEL_ORDER: [&str; 8] = [
+    "Name",
+    "Description",
+    "DifficultySetting",
+    "Resupply",
+    "Pools",
+    "EnemiesNoSync",
+    "Caps",
+    "EscortMule",
+];
+
+/// Top-level key of the object telling which converter produced a file, see [`Options::stamp`].
+/// Like `x-unconverted`, it is not a module, and CD2 leaves it alone.
+pub const STAMP_KEY: &str = "_cd2ifier";
+
+/// `CD2_module` of the pawn stats of the translation data that go directly into the enemy
+/// controls, like `AttackDamageMultiplier`, rather than into a module like `Resistances`.
+const ENEMY_CONTROLS_MODULE: &str = "None";
+
+/// Maximum number of resupplies the starting nitra can pay for in the nitra mutator, which holds
+/// one value per resupply. More starting nitra than that is most likely a mistake.
+const MAX_FREE_RESUPPLIES: usize = 100;
+
+/// A part of a CD1 file that the conversion translates on its own, see
+/// [`Options::skip_sections`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// StartingNitra and ResupplyCost, which become the Resupply module.
+    Resupply,
+    /// The fields going to the top modules, like DifficultySetting, Caps and Pools.
+    Top,
+    /// EnemyDescriptors, which become EnemiesNoSync.
+    Enemies,
+    /// The EscortMule fields.
+    Escort,
+}
+
+impl Section {
+    /// Every section, in the order they are converted.
+    pub const ALL: [Section; 4] = [
+        Section::Resupply,
+        Section::Top,
+        Section::Enemies,
+        Section::Escort,
+    ];
+
+    /// Top-level keys of a CD1 file that the section translates. The top section takes every
+    /// key that is not Name, Description or one of the other sections.
+    fn source_keys(self) -> &'static [&'static str] {
+        match self {
+            Section::Resupply => &["StartingNitra", "ResupplyCost"],
+            Section::Enemies => &["EnemyDescriptors"],
+            Section::Escort => &["EscortMule"],
+            Section::Top => &[],
+        }
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Section::Resupply => "resupply",
+            Section::Top => "top",
+            Section::Enemies => "enemies",
+            Section::Escort => "escort",
+        })
+    }
+}
+
+impl FromStr for Section {
+    type Err = String;
+    fn from_str(input: &str) -> std::result::Result<Section, Self::Err> {
+        Section::ALL
+            .into_iter()
+            .find(|section| section.to_string().eq_ignore_ascii_case(input))
+            .ok_or_else(|| {
+                format!("unknown section [{input}], expected resupply, top, enemies or escort")
+            })
+    }
+}
+
+/// Options controlling how [`convert_str_with`] writes the converted file.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Write the JSON in compact form instead of pretty-printing it. Takes precedence over
+    /// `indent`.
+    pub dont_pretty_print: bool,
+    /// Number of spaces per indentation level when pretty-printing, 4 by default.
+    pub indent: u16,
+    /// End the output with a line ending, as editors do, true by default.
+    pub final_newline: bool,
+    /// Add the `BaseHazard` default of the translation data when the input has none, true by
+    /// default. Without it, the host picks the hazard.
+    pub default_base_hazard: bool,
+    /// Add a [`STAMP_KEY`] object at the end of the output, holding the version of the crate and
+    /// a hash of the translation data, to tell later which converter produced a file. Off by
+    /// default so that the output of the library doesn't change with each release.
+    pub stamp: bool,
+    /// Keep the fields the conversion doesn't understand under an `x-unconverted` object, see
+    /// [`unconverted_fields`].
+    pub keep_unknown: bool,
+    /// Write the whole-line comments of the input back into the output, before the field they
+    /// were written before. The output is then no longer strict JSON. Ignored with
+    /// `dont_pretty_print`.
+    pub keep_comments: bool,
+    /// CD2 version the input was already migrated to. The translation rules introduced up to
+    /// that version are skipped. If not set, the `Version` field of the input is used when it is
+    /// a CD2 version tagging a rule of the translation data, with a warning.
+    pub since_version: Option<String>,
+    /// CD2 version the output is meant for. Only the translation rules meant for that version
+    /// are applied, which also picks the target of the pawn stats that moved between versions.
+    /// If not set, the latest version is targeted.
+    pub target_version: Option<String>,
+    /// Maximum nesting depth of objects and arrays in the input, checked before parsing it.
+    pub max_depth: Option<usize>,
+    /// Maximum size of the input in bytes, checked before parsing it.
+    pub max_bytes: Option<usize>,
+    /// Sort the `EnemiesNoSync` entries by enemy name, for diff-friendly output.
+    pub sort_enemies: bool,
+    /// With `sort_enemies`, also sort the controls of each enemy by name, at every level.
+    pub sort_keys: bool,
+    /// Check the converted document against the shape CD2 expects, reporting what doesn't fit
+    /// in [`ConversionReport::schema_violations`].
+    pub check_schema: bool,
+    /// Check the numbers of the converted document against the bounds of the `SANITY_BOUNDS`
+    /// of the translation data, reporting what is out of range in
+    /// [`ConversionReport::out_of_bounds_values`].
+    pub sanity_check: bool,
+    /// Collapse the single entry arrays of the converted document into plain values, like a
+    /// pool of a single enemy into its name. Arrays that can't be collapsed without guessing are
+    /// reported in [`ConversionReport::unflattened_values`].
+    pub flatten: bool,
+    /// Values forced in the converted document once it is built, whatever the input holds,
+    /// like a `BaseHazard` of 4. Missing objects on the way are created. The schema and bounds
+    /// checks see the overridden values.
+    pub overrides: Vec<Override>,
+    /// List the changes made by the conversion in [`ConversionReport::changelog`].
+    pub changelog: bool,
+    /// Sections of the input copied to the output as they are instead of being converted, for
+    /// migrating a file a part at a time.
+    pub skip_sections: Vec<Section>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            dont_pretty_print: false,
+            indent: 4,
+            final_newline: true,
+            default_base_hazard: true,
+            stamp: false,
+            keep_unknown: false,
+            keep_comments: false,
+            since_version: None,
+            target_version: None,
+            max_depth: None,
+            max_bytes: None,
+            sort_enemies: false,
+            sort_keys: false,
+            check_schema: false,
+            sanity_check: false,
+            flatten: false,
+            overrides: Vec::new(),
+            changelog: false,
+            skip_sections: Vec::new(),
+        }
+    }
+}
+
+struct DiffContainer<'a> {
+    new: JsonValue,
+    original: &'a JsonValue,
+    report: ConversionReport,
+    /// CD2 version the original was already migrated to, if any.
+    since_version: Option<&'a str>,
+    /// Whether the original looks like it was already partly converted to CD2.
+    partly_cd2: bool,
+    /// CD2 version the new document is meant for, the latest if not set.
+    target_version: Option<&'a str>,
+    /// Whether to add the default BaseHazard when the original has none.
+    default_base_hazard: bool,
+}
+
+impl<'a> DiffContainer<'a> {
+    fn new(
+        original: &'a JsonValue,
+        translation_data: &JsonValue,
+        options: &'a Options,
+        report: ConversionReport,
+    ) -> Self {
+        DiffContainer {
+            new: json::JsonValue::new_object(),
+            original,
+            report,
+            since_version: options.since_version.as_deref().or_else(|| {
+                original["Version"]
+                    .as_str()
+                    .filter(|version| is_known_version(translation_data, version))
+            }),
+            partly_cd2: looks_partly_cd2(original, translation_data),
+            target_version: options.target_version.as_deref(),
+            default_base_hazard: options.default_base_hazard,
+        }
+    }
+    // Each stage takes the container by value and updates the new document in place, so the
+    // document is never cloned between stages.
+    fn section(
+        self,
+        section: Section,
+        skipped: &[Section],
+        build: impl FnOnce(Self) -> Self,
+    ) -> Self {
+        if skipped.contains(&section) {
+            self.copy_section(section)
+        } else {
+            build(self)
+        }
+    }
+    fn copy_section(mut self, section: Section) -> Self {
+        event!(Level::INFO, "Copying the [{section}] section as it is.");
+        let is_other_section = |key: &str| {
+            matches!(key, "Name" | "Description")
+                || Section::ALL
+                    .iter()
+                    .any(|section| section.source_keys().contains(&key))
+        };
+        for (key, value) in self.original.entries() {
+            let in_section = match section {
+                Section::Top => !is_other_section(key),
+                _ => section.source_keys().contains(&key),
+            };
+            if in_section {
+                self.new[key] = value.clone();
+            }
+        }
+        self
+    }
+    fn copy_field_if_exists(mut self, field: &str, err_msg: Option<&str>) -> Self {
+        // An explicit null is no valid value for any of these fields, so it counts as missing:
+        if !self.original[field].is_null() {
+            self.new[field] = self.original[field].clone();
+        } else if let Some(msg) = err_msg {
+            event!(Level::WARN, "Field [{field}] was missing. [{msg}]");
+            self.report
+                .missing_recommended_fields
+                .push(field.to_string());
+        }
+        self
+    }
+    fn build_resupply_module(mut self) -> Self {
+        // Resupply module. Copy the cost if StartingNitra is 0 or missing, otherwise add
+        // the corresponding nitra mutator
+
+        fn compute_supply_vector(starting_nitra: f64, original_cost: f64) -> Vec<f64> {
+            if original_cost <= 0.0 {
+                // Free resupplies, as if the starting nitra paid for the most of them allowed:
+                vec![0.0; MAX_FREE_RESUPPLIES + 1]
+            } else if starting_nitra < original_cost {
+                vec![original_cost - starting_nitra, original_cost]
+            } else {
+                // The starting nitra pays for as many resupplies as it covers, then what is left
+                // lowers the cost of the next one. When nothing is left, as when the nitra is a
+                // multiple of the cost, there is no partial resupply: 2 * cost gives [0, 0, cost].
+                let remainder = starting_nitra % original_cost;
+                std::iter::repeat_n(0.0, (starting_nitra / original_cost) as usize)
+                    .chain((remainder > 0.0).then_some(original_cost - remainder))
+                    .chain([original_cost])
+                    .collect()
+            }
+        }
+
+        // A missing or null StartingNitra means no starting nitra, the same as 0. A missing or
+        // null ResupplyCost means the default cost of 80, while a cost of 0 makes resupplies free.
+        // Both can also be numbers written as strings, like "120".
+        let original_nitra = &self.original["StartingNitra"];
+        let starting_nitra = if original_nitra.is_null() {
+            0.0
+        } else if let Some(nitra) = original_nitra.as_f64().filter(|nitra| nitra.is_finite()) {
+            // An infinite starting nitra would pay for endless resupplies, so it is not used
+            nitra
+        } else if let Some(nitra) = numeric_string(original_nitra) {
+            self.resupply_warning(format!(
+                "StartingNitra is the string [{original_nitra}] instead of a number. Using it as [{nitra}]."
+            ));
+            nitra
+        } else {
+            self.resupply_warning(format!(
+                "Unsupported StartingNitra: [{original_nitra}]. Using no starting nitra."
+            ));
+            0.0
+        };
+        let no_starting_nitra = starting_nitra == 0.0;
+        let resupply_cost = &self.original["ResupplyCost"];
+        let original_resupply_cost: f64 = if resupply_cost.is_null() {
+            80.00
+        } else if let Some(cost) = resupply_cost.as_f64() {
+            cost
+        } else if let Some(cost) = numeric_string(resupply_cost) {
+            self.resupply_warning(format!(
+                "ResupplyCost is the string [{resupply_cost}] instead of a number. Using it as [{cost}]."
+            ));
+            cost
+        } else if is_weighted_bins(resupply_cost) && no_starting_nitra {
+            // Without StartingNitra the bins can be used as the CD2 cost directly:
+            self.new["Resupply"]["Cost"] = update_if_range_array("ResupplyCost", resupply_cost);
+            return self;
+        } else if let Some(cost) = weighted_mean(resupply_cost) {
+            // The nitra mutator needs a single cost, use the weighted mean of the bins:
+            self.resupply_warning(format!(
+                "ResupplyCost is a weighted array, which can't be combined with StartingNitra. Using its weighted mean [{cost}]."
+            ));
+            cost
+        } else {
+            self.resupply_warning(format!(
+                "Unsupported ResupplyCost: [{resupply_cost}]. Using the default cost of 80."
+            ));
+            80.00
+        };
+        if original_resupply_cost < 0.0 {
+            self.resupply_warning(format!(
+                "ResupplyCost is [{original_resupply_cost}], which is negative. Using a flat cost of 0."
+            ));
+            self.new["Resupply"]["Cost"] = 0.into();
+        } else if no_starting_nitra {
+            self.new["Resupply"]["Cost"] = original_resupply_cost.into();
+        } else {
+            // With free resupplies, the starting nitra pays for endless ones, so it is capped
+            // too, without dividing by the cost:
+            let starting_nitra = if original_resupply_cost == 0.0 {
+                self.resupply_warning(format!(
+                    "ResupplyCost is 0, so StartingNitra [{starting_nitra}] pays for endless resupplies. Making the first {MAX_FREE_RESUPPLIES} free."
+                ));
+                0.0
+            } else if starting_nitra / original_resupply_cost > MAX_FREE_RESUPPLIES as f64 {
+                let capped = MAX_FREE_RESUPPLIES as f64 * original_resupply_cost;
+                self.resupply_warning(format!(
+                    "StartingNitra [{starting_nitra}] pays for more than {MAX_FREE_RESUPPLIES} resupplies. Using [{capped}]."
+                ));
+                capped
+            } else {
+                starting_nitra
+            };
+            self.new["Resupply"]["Cost"] = object! {
+                "Mutate": "ByResuppliesCalled",
+                "Values": compute_supply_vector(starting_nitra, original_resupply_cost)
+            }
+        }
+        self
+    }
+    fn build_top_stage(self, translation_data: &JsonValue) -> Self {
+        self.build_top_modules(&translation_data["TOP_MODULES"])
+            .add_base_hazard(&translation_data["BASE_HAZARD_DEFAULT"])
+            .rename_pools(&translation_data["POOL_RENAMES"])
+    }
+    fn resupply_warning(&mut self, msg: String) {
+        event!(Level::WARN, "{msg}");
+        self.report.resupply_warnings.push(msg);
+    }
+    fn build_enemies_module(mut self, translation_data: &JsonValue) -> Self {
+        // Enemies module, copy as-is but fix the old pawn stats and remove deprecated fields:
+        if !self.original["EnemyDescriptors"].is_null() {
+            // An earlier stage may have built an EnemiesNoSync already, which the converted
+            // enemies are merged into once done:
+            let earlier = self.new.remove("EnemiesNoSync");
+            self.new["EnemiesNoSync"] = self.original["EnemyDescriptors"].clone();
+            // Fix pawn stats:
+            for (enemy, controls) in self.new["EnemiesNoSync"].entries_mut() {
+                let _span = span!(Level::INFO, "enemy", enemy = %enemy).entered();
+                self.report.converted_enemies.push(enemy.to_string());
+                if !controls["PawnStats"].is_null() {
+                    let pawn_stats = controls.remove("PawnStats");
+                    translate_pawn_stats(
+                        controls,
+                        &pawn_stats,
+                        &translation_data["PAWN_STATS"],
+                        enemy,
+                        self.partly_cd2,
+                        self.target_version,
+                        &mut self.report,
+                    );
+                }
+                // Rename the aliases and miscased controls, then remove deprecated fields:
+                let valid_controls = &translation_data["VALID_ENEMY_CONTROLS"];
+                for (field, _) in self.original["EnemyDescriptors"][enemy].entries() {
+                    if valid_controls.contains(field) || field == "PawnStats" {
+                        continue;
+                    }
+                    let canonical = translation_data["ENEMY_CONTROL_ALIASES"][field]
+                        .as_str()
+                        .or_else(|| {
+                            valid_controls
+                                .members()
+                                .filter_map(JsonValue::as_str)
+                                .find(|valid| valid.eq_ignore_ascii_case(field))
+                        });
+                    if let Some(canonical) =
+                        canonical.filter(|canonical| controls.has_key(canonical))
+                    {
+                        // Renaming would overwrite the control given under its canonical name,
+                        // which is the one kept:
+                        event!(
+                            Level::WARN,
+                            "Enemy control [{field}] in [{enemy}] is another name of [{canonical}], which is given too. Keeping [{canonical}] and skipping [{field}]."
+                        );
+                        controls.remove(field);
+                        self.report
+                            .deprecated_enemy_controls
+                            .push(EnemyField::new(enemy, field));
+                    } else if let Some(canonical) = canonical {
+                        event!(
+                            Level::INFO,
+                            "Enemy control [{field}] in [{enemy}] renamed to [{canonical}]."
+                        );
+                        controls[canonical] = controls.remove(field);
+                    } else {
+                        let suggestion = closest_match(field, valid_controls)
+                            .map(|close| format!(" Did you mean [{close}]?"))
+                            .unwrap_or_default();
+                        event!(
+                            Level::INFO,
+                            "Deprecated or mistyped enemy control: [{field}] in [{enemy}].{suggestion} Skipping."
+                        );
+                        controls.remove(field);
+                        self.report
+                            .deprecated_enemy_controls
+                            .push(EnemyField::new(enemy, field));
+                    }
+                }
+                // Catch mistyped bases, which would otherwise produce a broken difficulty:
+                if controls.has_key("Base")
+                    && !translation_data["KNOWN_BASE_ENEMIES"].contains(controls["Base"].clone())
+                {
+                    event!(
+                        Level::WARN,
+                        "Unknown base enemy: [{}] in [{enemy}]. Is it mistyped?",
+                        controls["Base"]
+                    );
+                    self.report.unknown_bases.push(UnknownBase {
+                        enemy: enemy.to_string(),
+                        base: controls["Base"].to_string(),
+                    });
+                }
+                // Elite detection. Elite can also be a probability or weighted bins, which CD2
+                // takes without their range part:
+                if controls.has_key("Elite") {
+                    controls["Elite"] =
+                        update_if_range_array(&format!("{enemy}.Elite"), &controls["Elite"]);
+                }
+                if can_be_elite(&controls["Elite"])
+                    && !(translation_data["VANILLA_ELITE_ENEMIES"])
+                        .contains(controls["Base"].clone())
+                    && (translation_data["VANILLA_ELITE_ENEMIES"]).contains(enemy)
+                {
+                    event!(
+                        Level::INFO,
+                        "Non-vanilla elite enemy detected with base: [{}]",
+                        controls["Base"].clone()
+                    );
+                    self.report.elite_base_overrides.push(EliteOverride {
+                        enemy: enemy.to_string(),
+                        base: controls["Base"].to_string(),
+                    });
+                    controls["ForceEliteBase"] = enemy.into();
+                } else if can_be_elite(&controls["Elite"])
+                    && !controls.has_key("ForceEliteBase")
+                    && !(translation_data["VANILLA_ELITE_ENEMIES"]).contains(enemy)
+                    && !(translation_data["VANILLA_ELITE_ENEMIES"])
+                        .contains(controls["Base"].clone())
+                {
+                    // Neither the enemy nor its base has an elite version in the game, so the
+                    // Elite control does nothing:
+                    event!(
+                        Level::WARN,
+                        "Enemy [{enemy}] can be elite but neither it nor its base [{}] is a vanilla elite enemy, so it will never be elite. Set its Base or ForceEliteBase to a vanilla elite enemy.",
+                        controls["Base"]
+                    );
+                    self.report.elites_without_base.push(enemy.to_string());
+                }
+            }
+            if !earlier.is_null() {
+                let converted = self.new.remove("EnemiesNoSync");
+                self.new["EnemiesNoSync"] = self.merge_enemies(earlier, converted);
+            }
+        }
+        self
+    }
+    /// Merges the converted enemies into an `EnemiesNoSync` built by an earlier stage. An enemy
+    /// on both sides is replaced by the converted one, which is warned about since the earlier
+    /// one is lost.
+    fn merge_enemies(&mut self, mut earlier: JsonValue, mut converted: JsonValue) -> JsonValue {
+        if !earlier.is_object() {
+            event!(
+                Level::WARN,
+                "EnemiesNoSync [{earlier}] built before the enemies is not an object and is replaced by the converted EnemyDescriptors."
+            );
+            self.report
+                .overwritten_enemies
+                .push("EnemiesNoSync".to_string());
+            return converted;
+        }
+        for (enemy, controls) in converted.entries_mut() {
+            if earlier.has_key(enemy) {
+                event!(
+                    Level::WARN,
+                    "Enemy [{enemy}] was already in EnemiesNoSync before the enemies were converted and is replaced by its EnemyDescriptors entry."
+                );
+                self.report.overwritten_enemies.push(enemy.to_string());
+            }
+            earlier[enemy] = controls.take();
+        }
+        earlier
+    }
+    fn prune_empty_modules(mut self) -> Self {
+        // CD2 treats an empty module differently from a missing one, so modules that got no
+        // field are left out:
+        let empty_modules: Vec<String> = self
+            .new
+            .entries()
+            .filter(|(_, value)| value.is_object() && value.is_empty())
+            .map(|(key, _)| key.to_string())
+            .collect();
+        for module in empty_modules {
+            event!(Level::DEBUG, "Leaving out the empty module [{module}].");
+            self.new.remove(&module);
+        }
+        self
+    }
+    fn order_top_level_keys(mut self) -> Self {
+        self.new = order_top_level_keys(self.new.take());
+        self
+    }
+    fn flatten(mut self, flatten: bool) -> Self {
+        if flatten {
+            for value in flatten::flatten(&mut self.new) {
+                event!(Level::WARN, "Ambiguous value to flatten: {value}.");
+                self.report.unflattened_values.push(value);
+            }
+        }
+        self
+    }
+    fn keep_unknown(mut self, keep_unknown: bool) -> Self {
+        if keep_unknown {
+            // Added before the checks, so that the schema check sees it:
+            let unconverted = unconverted_fields(self.original, &self.report);
+            if !unconverted.is_empty() {
+                self.new["x-unconverted"] = unconverted;
+            }
+        }
+        self
+    }
+    fn apply_overrides(mut self, overrides: &[Override]) -> Result<Self> {
+        for over in overrides {
+            pointer::apply(&mut self.new, over).map_err(|reason| {
+                ConversionError::InvalidOverride(format!(
+                    "The override of [{}] can't be applied: {reason}",
+                    over.pointer
+                ))
+            })?;
+            event!(Level::DEBUG, "Overrode [{}].", over.pointer);
+        }
+        Ok(self)
+    }
+    fn check_schema(mut self, check_schema: bool) -> Self {
+        if check_schema {
+            for violation in schema::violations(&self.new) {
+                event!(Level::WARN, "Schema violation: {violation}.");
+                self.report.schema_violations.push(violation);
+            }
+        }
+        self
+    }
+    fn check_bounds(mut self, sanity_check: bool, bounds: &JsonValue) -> Self {
+        if sanity_check {
+            for value in schema::out_of_bounds(&self.new, bounds) {
+                event!(Level::WARN, "Out of range value: {value}.");
+                self.report.out_of_bounds_values.push(value);
+            }
+        }
+        self
+    }
+    fn check_finite_numbers(mut self) -> Self {
+        // NaN and infinite numbers are written as null or as out of range literals, which the
+        // game can't read:
+        let mut paths = Vec::new();
+        non_finite_numbers(&self.new, String::new(), &mut paths);
+        for path in paths {
+            event!(
+                Level::WARN,
+                "The number at [{path}] is NaN or infinite, which CD2 can't read."
+            );
+            self.report.non_finite_numbers.push(path);
+        }
+        self
+    }
+    fn sort_enemies(mut self, sort_enemies: bool, sort_keys: bool) -> Self {
+        if !sort_enemies || !self.new.has_key("EnemiesNoSync") {
+            return self;
+        }
+        let enemies = &mut self.new["EnemiesNoSync"];
+        *enemies = sorted_by_key(enemies.take());
+        if sort_keys {
+            for (_, controls) in enemies.entries_mut() {
+                *controls = sorted_keys_deep(controls.take());
+            }
+        }
+        self
+    }
+    fn build_top_modules(mut self, top_modules_map: &JsonValue) -> Self {
+        for (original_key, original_value) in self.original.entries() {
+            if let Some(field_status) = top_modules_map[original_key].as_str() {
+                match FieldStatus::from_str(field_status).unwrap() {
+                    FieldStatus::Valid(top_module) => {
+                        self.new[top_module][original_key] =
+                            update_if_range_array(original_key, original_value);
+                        self.report.moved_fields.push(original_key.to_string());
+                    }
+                    FieldStatus::Deprecated => {
+                        event!(Level::INFO, "Deprecated field: [{original_key}]. Skipping.");
+                        self.report.deprecated_fields.push(original_key.to_string());
+                    }
+                    FieldStatus::Ignored => (),
+                }
+            } else {
+                self.report
+                    .unsupported_fields
+                    .push(original_key.to_string());
+            }
+        }
+        // A single warning for all the unsupported fields, so they don't get lost in the logs:
+        if !self.report.unsupported_fields.is_empty() {
+            event!(
+                Level::WARN,
+                "Unsupported fields: [{}]. Please open an issue.",
+                self.report.unsupported_fields.join(", ")
+            );
+        }
+        self
+    }
+    fn build_escort_module(mut self, escort_mule_map: &JsonValue) -> Self {
+        // The EscortMule fields are checked against ESCORT_MULE, which can rename them or drop
+        // the deprecated ones. The built-in data keeps every known field as it is, since no CD2
+        // rename of them is documented:
+        let escort_mule = &self.original["EscortMule"];
+        if !escort_mule.is_object() {
+            return self.copy_field_if_exists("EscortMule", None);
+        }
+        self.new["EscortMule"] = JsonValue::new_object();
+        for (field, value) in escort_mule.entries() {
+            let path = format!("EscortMule.{field}");
+            match escort_mule_map[field].as_str().map(FieldStatus::from_str) {
+                Some(Ok(FieldStatus::Valid(new_field))) => {
+                    self.new["EscortMule"][new_field] = update_if_range_array(&path, value);
+                }
+                Some(Ok(FieldStatus::Deprecated)) => {
+                    event!(Level::INFO, "Deprecated field: [{path}]. Skipping.");
+                    self.report.deprecated_fields.push(path);
+                }
+                Some(Ok(FieldStatus::Ignored)) => (),
+                _ => {
+                    event!(
+                        Level::WARN,
+                        "Unsupported field: [{path}]. Please open an issue."
+                    );
+                    self.report.unsupported_fields.push(path);
+                }
+            }
+        }
+        self
+    }
+    fn add_base_hazard(mut self, base_hazard_default: &JsonValue) -> Self {
+        // A hazard given in the source is kept, CD1 hazards given as a number are written the
+        // CD2 way:
+        let base_hazard = &self.new["DifficultySetting"]["BaseHazard"];
+        if let Some(hazard) = base_hazard.as_f64() {
+            self.new["DifficultySetting"]["BaseHazard"] = format!("Hazard {hazard}").into();
+        } else if base_hazard.is_null()
+            && self.default_base_hazard
+            && self.applies(base_hazard_default)
+        {
+            // Otherwise we add the BaseHazard field, defaults to Hazard 5 for explicitness:
+            self.new["DifficultySetting"]["BaseHazard"] = base_hazard_default["value"].clone();
+        }
+        self
+    }
+    fn rename_pools(mut self, pool_renames: &JsonValue) -> Self {
+        // Some pools changed name in CD2, like StationaryEnemies which became StationaryPool:
+        if !self.new.has_key("Pools") {
+            return self;
+        }
+        for (old_name, rename) in pool_renames.entries() {
+            let (Some(new_name), true) =
+                (rename["name"].as_str(), self.new["Pools"].has_key(old_name))
+            else {
+                continue;
+            };
+            if self.applies(rename) {
+                self.new["Pools"][new_name] = self.new["Pools"].remove(old_name);
+            }
+        }
+        self
+    }
+    /// Tells whether a translation rule has to be applied, which is the case if it is meant
+    /// for the target version, see [`is_for_target_version`], unless the original was already
+    /// migrated to the version that introduced it.
+    fn applies(&self, rule: &JsonValue) -> bool {
+        let migrated = match (self.since_version, min_version(rule)) {
+            (Some(since_version), Some(rule_version)) => {
+                parse_version(rule_version) <= parse_version(since_version)
+            }
+            _ => false,
+        };
+        !migrated && is_for_target_version(rule, self.target_version)
+    }
+}
+
+/// Detects fields that have weights, like `[{"weight": 1, "range": {"min": 1, "max": 2}}]`. The
+/// whole array is looked at, since some of its entries may lack the weight.
+fn is_weighted_bins(value: &JsonValue) -> bool {
+    value.is_array() && value.members().any(|bin| bin.has_key("weight"))
+}
+
+fn update_if_range_array(field: &str, original_value: &JsonValue) -> JsonValue {
+    // CD2 removes the "range" part of the weighted bins:
+    if !is_weighted_bins(original_value) {
+        return original_value.clone();
+    }
+    original_value
+        .members()
+        .enumerate()
+        .map(|(index, bin)| update_bin(&format!("{field}[{index}]"), bin))
+        .collect::<Vec<JsonValue>>()
+        .into()
+}
+
+/// Takes the range part out of a weighted bin. A bin lacking its weight gets a weight of 1, a
+/// plain number becomes a bin holding just that value, and a range with a single bound uses it
+/// for both. Bins with nothing usable are kept as they are.
+fn update_bin(path: &str, bin: &JsonValue) -> JsonValue {
+    // The values are cloned rather than read as floats, so that they are written as in the
+    // source file:
+    let number = |value: &JsonValue| value.is_number().then(|| value.clone());
+    let (min, max) = if bin.is_number() {
+        (number(bin), number(bin))
+    } else if bin.has_key("range") {
+        (number(&bin["range"]["min"]), number(&bin["range"]["max"]))
+    } else {
+        // Already in the CD2 form:
+        (number(&bin["min"]), number(&bin["max"]))
+    };
+    let (min, max) = match (min, max) {
+        (Some(min), Some(max)) => (min, max),
+        (Some(bound), None) | (None, Some(bound)) => {
+            event!(
+                Level::WARN,
+                "Weighted bin [{path}] has a single bound, using [{bound}] for both."
+            );
+            (bound.clone(), bound)
+        }
+        (None, None) => {
+            event!(
+                Level::WARN,
+                "Weighted bin [{path}] has no range. Keeping it as it is."
+            );
+            return bin.clone();
+        }
+    };
+    let weight = if bin.has_key("weight") {
+        bin["weight"].clone()
+    } else {
+        event!(
+            Level::WARN,
+            "Weighted bin [{path}] has no weight, using a weight of 1."
+        );
+        1.into()
+    };
+    object! {"weight": weight, "min": min, "max": max}
+}
+
+/// Mean of the middle of each range of weighted bins, weighted by the bin weights.
+fn weighted_mean(bins: &JsonValue) -> Option<f64> {
+    if !is_weighted_bins(bins) {
+        return None;
+    }
+    let (total, weights) = bins
+        .members()
+        .try_fold((0.0, 0.0), |(total, weights), bin| {
+            let weight = bin["weight"].as_f64()?;
+            let middle = (bin["range"]["min"].as_f64()? + bin["range"]["max"].as_f64()?) / 2.0;
+            Some((total + weight * middle, weights + weight))
+        })?;
+    (weights > 0.0).then(|| total / weights)
+}
+
+/// Tells whether an Elite control can make the enemy elite: true, a non-zero probability or
+/// weighted bins with some non-zero value. Weighted bins are expected in their CD2 form.
+fn can_be_elite(elite: &JsonValue) -> bool {
+    if let Some(probability) = elite.as_f64() {
+        probability > 0.0
+    } else if is_weighted_bins(elite) {
+        elite.members().any(|bin| {
+            bin["weight"].as_f64().is_some_and(|weight| weight > 0.0)
+                && bin["max"].as_f64().is_some_and(|max| max > 0.0)
+        })
+    } else {
+        *elite == true
+    }
+}
+
+/// The string of `candidates` closest to `name`, if any is close enough to be a likely typo.
+fn closest_match<'a>(name: &str, candidates: &'a JsonValue) -> Option<&'a str> {
+    candidates
+        .members()
+        .filter_map(JsonValue::as_str)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= 2.max(candidate.len() / 4))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings, ignoring case.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Splits a version like `1.2.3` into its numbers so that versions can be compared. Parts that
+/// are not numbers count as 0.
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches(['v', 'V'])
+        .split('.')
+        .map(|part| part.trim().parse().unwrap_or(0))
+        .collect()
+}
+
+/// Puts the top-level modules in the order of [`TOP_LEVEL_ORDER`], followed by any other key.
+fn order_top_level_keys(mut value: JsonValue) -> JsonValue {
+    if !value.is_object() {
+        return value;
+    }
+    // The json crate keeps the insertion order, so the keys are sorted by rebuilding the
+    // object. Fields inside each module keep the order of the source file.
+    let mut ordered = JsonValue::new_object();
+    for key in TOP_LEVEL_ORDER {
+        if value.has_key(key) {
+            ordered[key] = value.remove(key);
+        }
+    }
+    for (key, value) in value.entries_mut() {
+        ordered[key] = value.take();
+    }
+    ordered
+}
+
+/// Rebuilds an object with its keys sorted. Anything else is returned as is.
+fn sorted_by_key(mut value: JsonValue) -> JsonValue {
+    if !value.is_object() {
+        return value;
+    }
+    let mut entries: Vec<(String, JsonValue)> = value
+        .entries_mut()
+        .map(|(key, value)| (key.to_string(), value.take()))
+        .collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut sorted = JsonValue::new_object();
+    for (key, value) in entries {
+        sorted[key.as_str()] = value;
+    }
+    sorted
+}
+
+/// Same as [`sorted_by_key`], also sorting the objects nested in it, arrays included.
+fn sorted_keys_deep(mut value: JsonValue) -> JsonValue {
+    if value.is_array() {
+        for member in value.members_mut() {
+            *member = sorted_keys_deep(member.take());
+        }
+        return value;
+    }
+    let mut sorted = sorted_by_key(value);
+    for (_, value) in sorted.entries_mut() {
+        *value = sorted_keys_deep(value.take());
+    }
+    sorted
+}
+
+enum FieldStatus {
+    Deprecated,
+    Ignored,
+    Valid(String),
+}
+
+impl FromStr for FieldStatus {
+    type Err = ();
+    fn from_str(input: &str) -> Result<FieldStatus, Self::Err> {
+        match input {
+            "deprecated" => Ok(FieldStatus::Deprecated),
+            "ignore" => Ok(FieldStatus::Ignored),
+            _ => Ok(FieldStatus::Valid(input.to_string())),
+        }
+    }
+}
+
+/// Collects the dotted paths of the NaN and infinite numbers inside `value`, array members being
+/// given by their index.
+fn non_finite_numbers(value: &JsonValue, path: String, paths: &mut Vec<String>) {
+    let child_path = |key: &dyn fmt::Display| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match value {
+        JsonValue::Number(_) if value.as_f64().is_some_and(|number| !number.is_finite()) => {
+            paths.push(path);
+        }
+        JsonValue::Object(_) => {
+            for (key, member) in value.entries() {
+                non_finite_numbers(member, child_path(&key), paths);
+            }
+        }
+        JsonValue::Array(members) => {
+            for (index, member) in members.iter().enumerate() {
+                non_finite_numbers(member, child_path(&index), paths);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Turns the legacy form of EnemyDescriptors found in some CD1 exports, an array of enemies
+/// each with a `name`, into the usual object keyed by enemy name. Anything else is returned as
+/// it is.
+fn with_keyed_enemy_descriptors(mut cd1_json: JsonValue) -> JsonValue {
+    if !cd1_json["EnemyDescriptors"].is_array() {
+        return cd1_json;
+    }
+    event!(
+        Level::WARN,
+        "EnemyDescriptors is an array, a legacy form. Converting it to an object keyed by enemy name."
+    );
+    let mut keyed = JsonValue::new_object();
+    for (index, mut enemy) in cd1_json["EnemyDescriptors"]
+        .take()
+        .members_mut()
+        .map(JsonValue::take)
+        .enumerate()
+    {
+        let name_key = enemy
+            .entries()
+            .find(|(key, value)| key.eq_ignore_ascii_case("name") && value.is_string())
+            .map(|(key, _)| key.to_string());
+        let Some(name_key) = name_key else {
+            event!(
+                Level::WARN,
+                "Enemy [{index}] of the EnemyDescriptors array has no name. Skipping."
+            );
+            continue;
+        };
+        let name = enemy.remove(&name_key).take_string().unwrap_or_default();
+        if keyed.has_key(&name) {
+            event!(
+                Level::WARN,
+                "Enemy [{name}] is given several times in the EnemyDescriptors array. Keeping the last one."
+            );
+        }
+        keyed[name.as_str()] = enemy;
+    }
+    cd1_json["EnemyDescriptors"] = keyed;
+    cd1_json
+}
+
+/// The number held by a string like `"120"`, which some CD1 files use for numeric fields.
+fn numeric_string(value: &JsonValue) -> Option<f64> {
+    value
+        .as_str()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+}
+
+/// Whether the source looks like it was already, at least partly, converted to CD2: it has CD2
+/// top modules, a `StationaryPool` or enemies with CD2 modules. The `Version` field is no sign
+/// of it, since authors also use it for their own revision numbers.
+fn looks_partly_cd2(cd1_json: &JsonValue, translation_data: &JsonValue) -> bool {
+    let pawn_stats = &translation_data["PAWN_STATS"];
+    let has_cd2_enemy_module = cd1_json["EnemyDescriptors"]
+        .entries()
+        .flat_map(|(_, controls)| controls.entries())
+        .any(|(control, value)| {
+            value.is_object()
+                && pawn_stats
+                    .entries()
+                    .flat_map(|(_, mapping)| pawn_stat_targets(mapping))
+                    .any(|target| target["CD2_module"] == control)
+        });
+    has_cd2_top_module(cd1_json, translation_data)
+        || cd1_json.has_key("StationaryPool")
+        || has_cd2_enemy_module
+}
+
+/// Whether the source has a CD2 top module, like `DifficultySetting` or `EnemiesNoSync`.
+fn has_cd2_top_module(cd1_json: &JsonValue, translation_data: &JsonValue) -> bool {
+    let top_modules = &translation_data["TOP_MODULES"];
+    cd1_json.entries().any(|(key, value)| {
+        value.is_object()
+            && (matches!(key, "Resupply" | "EnemiesNoSync")
+                || top_modules.entries().any(|(_, module)| module == key))
+    })
+}
+
+/// Whether the source looks like a CD2 file rather than a CD1 one: it has CD2 top modules but
+/// no `EnemyDescriptors`. Converting it again would mangle it.
+fn looks_cd2(cd1_json: &JsonValue, translation_data: &JsonValue) -> bool {
+    !cd1_json.has_key("EnemyDescriptors") && has_cd2_top_module(cd1_json, translation_data)
+}
+
+/// Whether a pawn stat going to `module` is a CD1 resistance, which CD2 takes inverted, as a
+/// multiplier of the damage taken. PST_DamageResistance is the exception, being a multiplier
+/// already in CD1.
+fn is_inverted_resistance(module: &str, stat: &str) -> bool {
+    module == "Resistances" && stat != "PST_DamageResistance"
+}
+
+/// Every target of a pawn stat in the translation data. A stat is either mapped to a single
+/// target, `{"CD2_module": "Resistances", "CD2_field": "FireDamageMultiplier"}`, or to a list of
+/// targets tagged with the CD2 version from which each one applies, oldest first.
+fn pawn_stat_targets(mapping: &JsonValue) -> impl Iterator<Item = &JsonValue> {
+    mapping
+        .members()
+        .chain(mapping.is_object().then_some(mapping))
+}
+
+/// Target of a pawn stat for the given CD2 version, the latest if not set. None if the stat
+/// is only supported from a later version.
+fn pawn_stat_target<'a>(
+    mapping: &'a JsonValue,
+    target_version: Option<&str>,
+) -> Option<&'a JsonValue> {
+    pawn_stat_targets(mapping)
+        .filter(|target| is_for_target_version(target, target_version))
+        .last()
+}
+
+/// Whether a version tags a translation rule of the translation data, making it a CD2 version
+/// rather than, say, the revision number some authors keep in the `Version` field of their files.
+fn is_known_version(translation_data: &JsonValue, version: &str) -> bool {
+    fn tags(value: &JsonValue, version: &[u64]) -> bool {
+        match value {
+            JsonValue::Object(object) => object.iter().any(|(key, value)| {
+                (matches!(key, "version" | "min_version" | "max_version")
+                    && value
+                        .as_str()
+                        .is_some_and(|tag| parse_version(tag) == version))
+                    || tags(value, version)
+            }),
+            JsonValue::Array(members) => members.iter().any(|member| tags(member, version)),
+            _ => false,
+        }
+    }
+    tags(translation_data, &parse_version(version))
+}
+
+/// CD2 version introducing a translation rule, if it is tagged with one.
+fn min_version(rule: &JsonValue) -> Option<&str> {
+    rule["min_version"].as_str().or(rule["version"].as_str())
+}
+
+/// Whether a translation rule is meant for the given CD2 version, the latest if not set. A rule
+/// is tagged with the version introducing it, as `version` or `min_version`, and, if a later
+/// version replaced it, with the last version it is meant for as `max_version`. Untagged rules
+/// are meant for every version.
+fn is_for_target_version(rule: &JsonValue, target_version: Option<&str>) -> bool {
+    let max_version = rule["max_version"].as_str();
+    match target_version.map(parse_version) {
+        Some(target_version) => {
+            min_version(rule).is_none_or(|min_version| parse_version(min_version) <= target_version)
+                && max_version
+                    .is_none_or(|max_version| target_version <= parse_version(max_version))
+        }
+        // The latest version is past the last version of every replaced rule:
+        None => max_version.is_none(),
+    }
+}
+
+fn translate_pawn_stats(
+    controls: &mut JsonValue,
+    pawn_stats: &JsonValue,
+    pawn_stats_map: &JsonValue,
+    enemy: &str,
+    partly_cd2: bool,
+    target_version: Option<&str>,
+    report: &mut ConversionReport,
+) {
+    for (stat, value) in pawn_stats.entries() {
+        if let Some(target) = pawn_stat_target(&pawn_stats_map[stat], target_version) {
+            // Checked by `convert_with`, but the single stages take the data as it is:
+            let (Some(new_module), Some(new_field)) =
+                (target["CD2_module"].as_str(), target["CD2_field"].as_str())
+            else {
+                event!(
+                    Level::WARN,
+                    "Pawn stat [{stat}] on enemy [{enemy}] has no CD2_module or CD2_field in the translation data. Skipping."
+                );
+                report
+                    .unsupported_pawn_stats
+                    .push(EnemyField::new(enemy, stat));
+                continue;
+            };
+            // CD1 resistances tell how much damage is resisted while CD2 uses multipliers of
+            // the damage taken, so a resistance R becomes a multiplier 1 - R. The exception is
+            // PST_DamageResistance, which was already a multiplier in CD1. A resistance above 1
+            // would give a negative multiplier, which is clamped to 0 (immune), and a negative
+            // resistance gives a multiplier above 1 (a weakness). Both are warned about, since
+            // they are most likely mistakes.
+            let new_value = if !is_inverted_resistance(new_module, stat) {
+                value
+            } else {
+                // Only scalar resistances can be inverted. Anything else, like the scaling
+                // curves of some newer CD1 files, has no CD2 multiplier to go to:
+                let Some(resistance) = value.as_f64() else {
+                    event!(
+                        Level::WARN,
+                        "Resistance [{stat}] on enemy [{enemy}] is not a number and can't be inverted into a multiplier. Skipping."
+                    );
+                    report
+                        .unsupported_pawn_stats
+                        .push(EnemyField::new(enemy, stat));
+                    continue;
+                };
+                let multiplier = 1.0 - resistance;
+                if !(0.0..=1.0).contains(&multiplier) {
+                    event!(
+                        Level::WARN,
+                        "Resistance [{stat}] on enemy [{enemy}] is [{value}], outside of the expected 0 to 1 range. Its multiplier [{multiplier}] {}.",
+                        if multiplier < 0.0 { "is clamped to 0" } else { "is kept as a weakness" }
+                    );
+                    report
+                        .out_of_range_resistances
+                        .push(EnemyField::new(enemy, stat));
+                } else if partly_cd2 {
+                    // A source that was already converted, even partly, may hold multipliers
+                    // that are inverted a second time here:
+                    event!(
+                        Level::WARN,
+                        "Resistance [{stat}] on enemy [{enemy}] is inverted to [{multiplier}], but the file looks partly converted to CD2 already. Check it wasn't a CD2 multiplier."
+                    );
+                    report
+                        .possibly_inverted_resistances
+                        .push(EnemyField::new(enemy, stat));
+                }
+                &multiplier.max(0.0).into()
+            };
+            let target = if new_module == ENEMY_CONTROLS_MODULE {
+                controls[new_field] = new_value.clone();
+                new_field.to_string()
+            } else {
+                controls[new_module][new_field] = new_value.clone();
+                format!("{new_module}.{new_field}")
+            };
+            // Two stats going to the same control, which aliases make possible, keep the last
+            // value only:
+            if let Some(index) = report
+                .remapped_pawn_stats
+                .iter()
+                .position(|remapped| remapped.enemy == enemy && remapped.target == target)
+            {
+                let overwritten = report.remapped_pawn_stats.remove(index);
+                event!(
+                    Level::WARN,
+                    "Pawn stats [{}] and [{stat}] on enemy [{enemy}] both go to [{target}]. The value of [{}] is overwritten.",
+                    overwritten.stat,
+                    overwritten.stat
+                );
+                report.overwritten_pawn_stats.push(overwritten);
+            }
+            report.remapped_pawn_stats.push(RemappedPawnStat {
+                enemy: enemy.to_string(),
+                stat: stat.to_string(),
+                target,
+            });
+        } else if let Some(version) = pawn_stat_targets(&pawn_stats_map[stat])
+            .filter_map(min_version)
+            .find(|version| {
+                target_version.is_some_and(|target_version| {
+                    parse_version(version) > parse_version(target_version)
+                })
+            })
+        {
+            event!(
+                Level::WARN,
+                "Pawn stat [{stat}] on enemy [{enemy}] is only supported from CD2 version [{version}]. Skipping."
+            );
+            report
+                .unsupported_pawn_stats
+                .push(EnemyField::new(enemy, stat));
+        } else if let Some(version) = pawn_stat_targets(&pawn_stats_map[stat])
+            .filter_map(|target| target["max_version"].as_str())
+            .last()
+        {
+            event!(
+                Level::WARN,
+                "Pawn stat [{stat}] on enemy [{enemy}] is only supported up to CD2 version [{version}]. Skipping."
+            );
+            report
+                .unsupported_pawn_stats
+                .push(EnemyField::new(enemy, stat));
+        } else {
+            event!(
+                Level::WARN,
+                "Unsupported pawn stat: [{stat}] on enemy [{enemy}]. Please open an issue. Skipping."
+            );
+            report
+                .unsupported_pawn_stats
+                .push(EnemyField::new(enemy, stat));
+        }
+    }
+}
+
+/// Removes the byte order mark some Windows editors put at the start of UTF-8 files, which the
+/// parser rejects.
+fn strip_bom(file_str: &str) -> &str {
+    file_str.strip_prefix('\u{feff}').unwrap_or(file_str)
+}
+
+fn parse_json(file_str: &str) -> Result<JsonValue> {
+    let file_str = lenient::strip_comments_and_trailing_commas(strip_bom(file_str));
+    duplicates::warn_duplicate_keys(&file_str);
+    Ok(json::parse(&file_str)?)
+}
+
+/// The duplicated keys of the text of a file, which the parsed document can't tell, see
+/// [`duplicates::find_duplicate_keys`].
+fn duplicate_keys(file_str: &str) -> Vec<String> {
+    duplicates::find_duplicate_keys(&lenient::strip_comments_and_trailing_commas(strip_bom(
+        file_str,
+    )))
+}
+
+/// Parses translation data in the format of `cd2-modules.json`, checking that it has all the
+/// keys the conversion relies on.
+pub fn parse_translation_data(data: &str) -> Result<JsonValue> {
+    let translation_data = parse_json(data)?;
+    let missing_keys: Vec<String> = TRANSLATION_DATA_KEYS
+        .iter()
+        .filter(|key| !translation_data.has_key(key))
+        .map(|key| key.to_string())
+        .collect();
+    if !missing_keys.is_empty() {
+        return Err(ConversionError::MissingTranslationData(missing_keys));
+    }
+    check_pawn_stat_targets(&translation_data)?;
+    Ok(translation_data)
+}
+
+/// Checks that every pawn stat target of the translation data has a field and goes either to a
+/// valid enemy control, like `Resistances`, or directly into the controls, so that a mistyped
+/// module fails here rather than ending up in every converted file. Each resistance must have a
+/// field of its own, since two resistances of an enemy going to the same multiplier would
+/// overwrite each other.
+fn check_pawn_stat_targets(translation_data: &JsonValue) -> Result<()> {
+    let valid_controls = &translation_data["VALID_ENEMY_CONTROLS"];
+    let mut resistance_fields: Vec<(&str, &str)> = Vec::new();
+    for (stat, mapping) in translation_data["PAWN_STATS"].entries() {
+        for target in pawn_stat_targets(mapping) {
+            let reason = match (target["CD2_module"].as_str(), target["CD2_field"].as_str()) {
+                (Some("Resistances"), Some(field)) => {
+                    match resistance_fields.iter().find(|(other_stat, other_field)| {
+                        *other_stat != stat && *other_field == field
+                    }) {
+                        Some((other_stat, _)) => format!(
+                            "goes to [Resistances.{field}], like the pawn stat [{other_stat}]"
+                        ),
+                        None => {
+                            resistance_fields.push((stat, field));
+                            continue;
+                        }
+                    }
+                }
+                (Some(module), Some(_))
+                    if module == ENEMY_CONTROLS_MODULE || valid_controls.contains(module) =>
+                {
+                    continue
+                }
+                (Some(module), Some(_)) => format!(
+                    "goes to the unknown CD2 module [{module}]. Use a module of \
+                    VALID_ENEMY_CONTROLS, or \"{ENEMY_CONTROLS_MODULE}\" for the enemy controls \
+                    themselves"
+                ),
+                _ => "lacks a CD2_module or a CD2_field string".to_string(),
+            };
+            return Err(ConversionError::InvalidTranslationData(format!(
+                "The pawn stat [{stat}] of the translation data {reason}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the translation data built into the crate.
+pub fn default_translation_data() -> Result<JsonValue> {
+    parse_translation_data(TRANSLATION_DATA)
+}
+
+/// Checks the input against the size and depth limits of the options, so that untrusted input
+/// gets a clean error instead of exhausting the memory or the stack of the parser.
+fn check_limits(input: &str, options: &Options) -> Result<()> {
+    if let Some(max_bytes) = options.max_bytes {
+        if input.len() > max_bytes {
+            return Err(ConversionError::InvalidSource(format!(
+                "The file is {} bytes long, more than the limit of {max_bytes} bytes",
+                input.len()
+            )));
+        }
+    }
+    if let Some(max_depth) = options.max_depth {
+        let (mut depth, mut in_string, mut escaped) = (0_usize, false, false);
+        for ch in input.chars() {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if ch == '\\' {
+                    escaped = true;
+                } else if ch == '"' {
+                    in_string = false;
+                }
+                continue;
+            }
+            match ch {
+                '"' => in_string = true,
+                '{' | '[' => {
+                    depth += 1;
+                    if depth > max_depth {
+                        return Err(ConversionError::InvalidSource(format!(
+                            "The file is nested deeper than the limit of {max_depth} levels"
+                        )));
+                    }
+                }
+                '}' | ']' => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_json_str_with_multilines(file_str: &str) -> Result<(JsonValue, Multilines)> {
+    // The BOM goes before extracting the multilines so that it doesn't end up in their lines:
+    let (file_str, multilines) = multilines::extract(strip_bom(file_str));
+    Ok((parse_json(&file_str)?, multilines))
+}
+
+/// Collects the comments of the input if the options keep them.
+fn comments(file_str: &str, options: &Options) -> Option<Comments> {
+    if !options.keep_comments {
+        return None;
+    }
+    if options.dont_pretty_print {
+        event!(Level::INFO, "Comments can't be kept in compact output.");
+        return None;
+    }
+    Some(comments::extract(strip_bom(file_str)))
+}
+
+fn render(
+    mut new: JsonValue,
+    multilines: &Multilines,
+    comments: Option<&Comments>,
+    options: &Options,
+) -> String {
+    let mut output = if options.dont_pretty_print {
+        multilines.append_to(&mut new);
+        json::stringify(new)
+    } else {
+        multilines.recover(
+            &json::stringify_pretty(new, options.indent),
+            options.indent.into(),
+        )
+    };
+    if let Some(comments) = comments {
+        output = comments.reattach(&output, multilines.line_ending());
+    }
+    if options.final_newline {
+        output.push_str(multilines.line_ending());
+    }
+    output
+}
+
+/// Converts a parsed CD1 document to CD2 using the given translation data, returning it along
+/// with the report of everything that was skipped on the way.
+pub fn convert(
+    cd1_json: &JsonValue,
+    translation_data: &JsonValue,
+) -> Result<(JsonValue, ConversionReport)> {
+    convert_with(cd1_json, translation_data, &Options::default())
+}
+
+/// Same as [`convert`], with the options that affect the translation itself, like
+/// `since_version`. Translation data whose pawn stats have no valid target is refused with
+/// [`ConversionError::InvalidTranslationData`].
+pub fn convert_with(
+    cd1_json: &JsonValue,
+    translation_data: &JsonValue,
+    options: &Options,
+) -> Result<(JsonValue, ConversionReport)> {
+    // The translation data may not come from `parse_translation_data`:
+    check_pawn_stat_targets(translation_data)?;
+    let keyed;
+    let cd1_json = if cd1_json["EnemyDescriptors"].is_array() {
+        keyed = with_keyed_enemy_descriptors(cd1_json.clone());
+        &keyed
+    } else {
+        cd1_json
+    };
+    let container = DiffContainer::new(
+        cd1_json,
+        translation_data,
+        options,
+        ConversionReport::default(),
+    );
+    match (container.since_version, &options.since_version) {
+        (Some(version), Some(_)) => event!(
+            Level::INFO,
+            "Skipping the translation rules up to CD2 version [{version}]."
+        ),
+        (Some(version), None) => event!(
+            Level::WARN,
+            "The Version field of the file says it was already migrated to CD2 version [{version}]: skipping the translation rules up to it, like the default BaseHazard."
+        ),
+        (None, _) => {
+            if let Some(version) = cd1_json["Version"].as_str() {
+                event!(
+                    Level::INFO,
+                    "The Version field of the file, [{version}], is not a known CD2 version. Applying every translation rule."
+                );
+            }
+        }
+    }
+    let already_cd2 = looks_cd2(cd1_json, translation_data);
+    if already_cd2 {
+        event!(
+            Level::WARN,
+            "The file looks like it is already a CD2 file: it has CD2 modules but no EnemyDescriptors. Converting it again will mangle it."
+        );
+    }
+    let skipped = &options.skip_sections;
+    let converted = container
+        .copy_field_if_exists("Name", "It is recommended to add a Name.".into())
+        .copy_field_if_exists(
+            "Description",
+            "It is recommended to add a Description.".into(),
+        )
+        .section(
+            Section::Resupply,
+            skipped,
+            DiffContainer::build_resupply_module,
+        )
+        .section(Section::Top, skipped, |container| {
+            container.build_top_stage(translation_data)
+        })
+        .section(Section::Enemies, skipped, |container| {
+            container
+                .build_enemies_module(translation_data)
+                .sort_enemies(options.sort_enemies, options.sort_keys)
+        })
+        .section(Section::Escort, skipped, |container| {
+            container.build_escort_module(&translation_data["ESCORT_MULE"])
+        })
+        .prune_empty_modules()
+        .order_top_level_keys()
+        .flatten(options.flatten)
+        .keep_unknown(options.keep_unknown)
+        .apply_overrides(&options.overrides)?
+        .check_schema(options.check_schema)
+        .check_bounds(options.sanity_check, &translation_data["SANITY_BOUNDS"])
+        .check_finite_numbers();
+    let mut report = converted.report;
+    report.already_cd2 = already_cd2;
+    if options.changelog {
+        report.changelog =
+            changelog::changelog(cd1_json, &converted.new, &report, translation_data);
+    }
+    Ok((converted.new, report))
+}
+
+/// Runs a single stage of the conversion on a CD1 document, returning the part of the CD2
+/// document it builds. What the stage skips is added to `report`.
+fn convert_stage(
+    cd1_json: &JsonValue,
+    translation_data: &JsonValue,
+    report: &mut ConversionReport,
+    stage: impl FnOnce(DiffContainer) -> DiffContainer,
+) -> JsonValue {
+    let keyed = with_keyed_enemy_descriptors(cd1_json.clone());
+    let options = Options::default();
+    let container = DiffContainer::new(&keyed, translation_data, &options, mem::take(report));
+    let converted = stage(container);
+    *report = converted.report;
+    converted.new
+}
+
+/// Converts only StartingNitra and ResupplyCost of a CD1 document, returning a CD2 document
+/// holding just the `Resupply` module, for tools migrating a file a part at a time.
+pub fn convert_resupply(cd1_json: &JsonValue, report: &mut ConversionReport) -> JsonValue {
+    convert_stage(cd1_json, &JsonValue::Null, report, |container| {
+        container.build_resupply_module()
+    })
+}
+
+/// Converts only the fields of a CD1 document going to the top modules, like
+/// DifficultySetting, Caps and Pools, returning a CD2 document holding just those modules.
+pub fn convert_top_modules(
+    cd1_json: &JsonValue,
+    translation_data: &JsonValue,
+    report: &mut ConversionReport,
+) -> JsonValue {
+    convert_stage(cd1_json, translation_data, report, |container| {
+        container.build_top_stage(translation_data)
+    })
+}
+
+/// Converts only the EnemyDescriptors of a CD1 document, returning a CD2 document holding just
+/// `EnemiesNoSync`.
+pub fn convert_enemies(
+    cd1_json: &JsonValue,
+    translation_data: &JsonValue,
+    report: &mut ConversionReport,
+) -> JsonValue {
+    convert_stage(cd1_json, translation_data, report, |container| {
+        container.build_enemies_module(translation_data)
+    })
+}
+
+/// Converts only the EscortMule of a CD1 document, returning a CD2 document holding just
+/// `EscortMule`.
+pub fn convert_escort(
+    cd1_json: &JsonValue,
+    translation_data: &JsonValue,
+    report: &mut ConversionReport,
+) -> JsonValue {
+    convert_stage(cd1_json, translation_data, report, |container| {
+        container.build_escort_module(&translation_data["ESCORT_MULE"])
+    })
+}
+
+/// Collects the fields of a CD1 document that its conversion dropped without translating them:
+/// the unsupported top-level fields, the deprecated enemy controls and the unsupported pawn
+/// stats. They keep their place in the structure of the source file, so that they can be
+/// migrated by hand later.
+pub fn unconverted_fields(cd1_json: &JsonValue, report: &ConversionReport) -> JsonValue {
+    let mut unconverted = JsonValue::new_object();
+    for field in &report.unsupported_fields {
+        match field.split_once('.') {
+            Some((module, field)) => unconverted[module][field] = cd1_json[module][field].clone(),
+            None => unconverted[field.as_str()] = cd1_json[field.as_str()].clone(),
+        }
+    }
+    let enemies = &cd1_json["EnemyDescriptors"];
+    for EnemyField { enemy, field } in &report.deprecated_enemy_controls {
+        unconverted["EnemyDescriptors"][enemy.as_str()][field.as_str()] =
+            enemies[enemy.as_str()][field.as_str()].clone();
+    }
+    for EnemyField { enemy, field } in &report.unsupported_pawn_stats {
+        unconverted["EnemyDescriptors"][enemy.as_str()]["PawnStats"][field.as_str()] =
+            enemies[enemy.as_str()]["PawnStats"][field.as_str()].clone();
+    }
+    unconverted
+}
+
+/// The stamp of a file converted with the translation data: the version of the crate and a hash
+/// of the translation data, which has no version of its own.
+fn stamp(translation_data: &JsonValue) -> JsonValue {
+    // FNV-1a, which unlike the hashers of std gives the same hash on every platform and release:
+    let hash = translation_data
+        .dump()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    object! {
+        "version": env!("CARGO_PKG_VERSION"),
+        "translation_data": format!("{hash:016x}"),
+    }
+}
+
+/// Converts the text of a CD1 file to CD2 with the built-in translation data, returning the
+/// pretty-printed result.
+pub fn convert_str(input: &str) -> Result<String> {
+    let (output, _) = convert_str_with(input, &default_translation_data()?, &Options::default())?;
+    Ok(output)
+}
+
+/// Converts the text of a CD1 file to CD2 with the given translation data and options, returning
+/// it along with the conversion report. Multiline descriptions are extracted before parsing and
+/// recovered in the result.
+pub fn convert_str_with(
+    input: &str,
+    translation_data: &JsonValue,
+    options: &Options,
+) -> Result<(String, ConversionReport)> {
+    check_limits(input, options)?;
+    let (cd1_json, multilines) = parse_json_str_with_multilines(input)?;
+    let (mut new, mut report) = convert_with(&cd1_json, translation_data, options)?;
+    report.duplicate_keys = duplicate_keys(input);
+    if new.has_key("x-unconverted") {
+        // Multiline fields are only recovered at the top level, so the unknown ones kept with
+        // `Options::keep_unknown` get their lines back as escaped line endings:
+        multilines.append_to(&mut new["x-unconverted"]);
+    }
+    if options.stamp {
+        new[STAMP_KEY] = stamp(translation_data);
+    }
+    let comments = comments(input, options);
+    Ok((render(new, &multilines, comments.as_ref(), options), report))
+}
+
+/// Merges the files named by the `"$include": "path.json"` directives of the text of a CD1 file
+/// into it, returning the text to convert. Directives can be in any object and name a file or a
+/// list of them, whose objects are merged into the object, its own keys taking precedence.
+/// Paths are relative to the including file, `source` for the top one, and the files are read
+/// with `load`. Cycles and includes nested deeper than [`MAX_INCLUDE_DEPTH`] are refused. A
+/// file without directives is returned as it is.
+pub fn resolve_includes_str(
+    input: &str,
+    source: &Path,
+    mut load: impl FnMut(&Path) -> io::Result<String>,
+) -> Result<String> {
+    if !input.contains(includes::INCLUDE_KEY) {
+        return Ok(input.to_string());
+    }
+    let (mut cd1_json, multilines) = parse_json_str_with_multilines(input)?;
+    includes::Resolver::new(source, &mut load).resolve(&mut cd1_json)?;
+    Ok(multilines.recover(&json::stringify_pretty(cd1_json, 4), 4))
+}
+
+/// Rewrites the text of a CD2 file with the indentation of the options and the top-level modules
+/// in the standard order, without translating anything. Multiline descriptions are kept.
+pub fn reformat_str(input: &str, options: &Options) -> Result<String> {
+    check_limits(input, options)?;
+    let (cd2_json, multilines) = parse_json_str_with_multilines(input)?;
+    let comments = comments(input, options);
+    Ok(render(
+        order_top_level_keys(cd2_json),
+        &multilines,
+        comments.as_ref(),
+        options,
+    ))
+}
+
+/// A CD2 file split into one file per top module by [`split_str`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitFile {
+    /// Text of the index file, holding the top-level fields that are not modules, like the
+    /// Name, and an `$include` of every module file.
+    pub index: String,
+    /// Path of each module file, relative to the index file, along with its text.
+    pub modules: Vec<(String, String)>,
+}
+
+/// Splits the text of a CD2 file into one file per top module, like `DifficultySetting`, each
+/// holding an object with the module alone, written in `module_dir`. The index file includes
+/// them all, so that resolving its includes with [`resolve_includes_str`] gives the CD2 file
+/// back. Multiline descriptions stay in the index.
+pub fn split_str(input: &str, module_dir: &str, options: &Options) -> Result<SplitFile> {
+    check_limits(input, options)?;
+    let (mut cd2_json, multilines) = parse_json_str_with_multilines(input)?;
+    let mut index = JsonValue::new_object();
+    let mut modules = Vec::new();
+    let mut includes = Vec::new();
+    for (key, value) in cd2_json.entries_mut() {
+        // The stamp describes the whole file rather than being a module:
+        if !value.is_object() || key == STAMP_KEY {
+            index[key] = value.take();
+            continue;
+        }
+        let path = format!("{module_dir}/{key}.json");
+        let mut module = JsonValue::new_object();
+        module[key] = value.take();
+        modules.push((
+            path.clone(),
+            render(module, &Multilines::default(), None, options),
+        ));
+        includes.push(path);
+    }
+    if !includes.is_empty() {
+        index[includes::INCLUDE_KEY] = includes.into();
+    }
+    Ok(SplitFile {
+        index: render(index, &multilines, None, options),
+        modules,
+    })
+}
+
+/// Compares the text of a converted file with a reference file, like a previously converted
+/// version of it, returning what differs structurally. Multiline descriptions are compared by
+/// value, and the [`STAMP_KEY`] objects are left out since they differ between releases.
+pub fn diff_str(reference: &str, input: &str) -> Result<Vec<Difference>> {
+    let parse = |file_str| {
+        let (mut json, multilines) = parse_json_str_with_multilines(file_str)?;
+        multilines.append_to(&mut json);
+        json.remove(STAMP_KEY);
+        Ok::<_, ConversionError>(json)
+    };
+    Ok(diff(&parse(reference)?, &parse(input)?))
+}
+
+/// Converts the text of a CD2 file back to CD1 with the given translation data and options,
+/// returning it along with the report of everything that had no CD1 equivalent.
+pub fn reverse_convert_str_with(
+    input: &str,
+    translation_data: &JsonValue,
+    options: &Options,
+) -> Result<(String, ConversionReport)> {
+    check_limits(input, options)?;
+    let (cd2_json, multilines) = parse_json_str_with_multilines(input)?;
+    let (new, mut report) = reverse_convert(&cd2_json, translation_data)?;
+    report.duplicate_keys = duplicate_keys(input);
+    let comments = comments(input, options);
+    Ok((render(new, &multilines, comments.as_ref(), options), report))
+}
@@ -1,404 +1,1731 @@
-use anyhow::{Context, Result};
-use clap::Parser;
-use itertools::{Either, Itertools};
+use anyhow::{bail, Context, Result};
+use cd2ifier::{Change, ConversionError, ConversionReport, Difference, Options, Override, Section};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Arg, CommandFactory, Parser, ValueEnum};
+use clap_complete::Shell;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use indicatif::{ProgressBar, ProgressStyle};
 use json::{object, JsonValue};
-use std::fs;
-use std::path::Path;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::process::{self, ExitCode};
 use std::str::FromStr;
-use std::{borrow::Cow, io::IsTerminal};
-use tracing::{event, Level};
-
-struct DiffContainer<'a> {
-    new: JsonValue,
-    original: &'a JsonValue,
-}
-
-impl<'a> DiffContainer<'a> {
-    fn copy_field_if_exists(self, field: &str, err_msg: Option<&str>) -> Self {
-        if self.original.has_key(field) {
-            let mut new = self.new.clone();
-            new[field] = self.original[field].clone();
-            DiffContainer {
-                new,
-                original: self.original,
-            }
-        } else {
-            if let Some(msg) = err_msg {
-                event!(Level::WARN, "Field [{field}] was missing. [{msg}]");
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::{
+    borrow::Cow,
+    io::{self, IsTerminal, Read, Write},
+};
+use tracing::{event, span, Level};
+use tracing_subscriber::filter::dynamic_filter_fn;
+use tracing_subscriber::prelude::*;
+
+mod archive;
+
+/// Path standing for stdin when used as source and for stdout when used as target.
+const STDIO: &str = "-";
+/// First bytes of every gzip file.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Context attached to the errors raised when writing the converted file, so they can be told
+/// apart from read and parse errors when choosing the exit code.
+#[derive(Debug)]
+struct WriteFailure(String);
+
+impl fmt::Display for WriteFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "There was a problem when writing to the final file {}",
+            self.0
+        )
+    }
+}
+
+/// Most verbose level of the diagnostics written to stderr.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => Level::ERROR,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Trace => Level::TRACE,
+        }
+    }
+}
+
+/// Target of the events summing up the run, which are still shown with `--quiet`.
+const SUMMARY: &str = "cd2ifier::summary";
+
+/// Format of the diagnostics written to stderr.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum LogFormat {
+    /// Human readable lines.
+    Text,
+    /// One JSON object per event, for log aggregation.
+    Json,
+}
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Paths to the CD1 files to be converted. A directory or a .zip archive stands for every
+    /// JSON file inside it. Use - to read from stdin. An http:// or https:// URL is downloaded.
+    #[arg(
+        required_unless_present = "generate_completions",
+        value_name = "SOURCE_FILE"
+    )]
+    source_files: Vec<String>,
+    /// Path where the translated CD2 file will be written to, when converting a single file. If
+    /// not specified, the script will append .cd2 to the original file name, or write to stdout
+    /// when reading from stdin. Use - to write to stdout
+    #[arg(
+        short = 'o',
+        long = "output",
+        value_name = "FILE",
+        conflicts_with = "output_dir"
+    )]
+    target_file: Option<String>,
+    /// Suffix inserted before the extension of the derived target file names, .cd2 (.cd1 with
+    /// --reverse) if not specified. Can be empty, with --output-dir, to keep the source names.
+    #[arg(long, value_name = "SUFFIX", env = "CD2IFIER_OUTPUT_SUFFIX")]
+    output_suffix: Option<String>,
+    /// Extension of the derived target file names, instead of the one of the source. Can be
+    /// empty for no extension.
+    #[arg(long, value_name = "EXTENSION")]
+    output_extension: Option<String>,
+    /// Directory where the converted files are written, instead of next to their sources. When
+    /// converting a directory, its subdirectories are recreated inside it.
+    #[arg(long, value_name = "DIR", env = "CD2IFIER_OUTPUT_DIR")]
+    output_dir: Option<String>,
+    /// If specified, the JSON will be written in compact form.
+    #[arg(short, long)]
+    dont_pretty_print: bool,
+    /// Number of spaces per indentation level of the pretty-printed JSON. Ignored with
+    /// --dont-pretty-print.
+    #[arg(long, value_name = "N", default_value_t = 4, env = "CD2IFIER_INDENT")]
+    indent: u16,
+    /// If specified, the converted file doesn't end with a newline.
+    #[arg(long)]
+    no_final_newline: bool,
+    /// If specified, no BaseHazard is added to the converted files that don't set one, so that
+    /// the host picks the hazard. A BaseHazard given in the source is always kept.
+    #[arg(long)]
+    no_base_hazard: bool,
+    /// If specified, the converted files are not stamped with a _cd2ifier object holding the
+    /// version of cd2ifier and a hash of the translation data that produced them.
+    #[arg(long)]
+    no_stamp: bool,
+    /// If specified, the fields that can't be converted are kept under an x-unconverted object,
+    /// instead of being dropped.
+    #[arg(long)]
+    keep_unknown: bool,
+    /// If specified, the comments before the top-level fields and enemies of the source file are
+    /// written back into the converted file, which is then no longer strict JSON.
+    #[arg(long)]
+    keep_comments: bool,
+    /// If specified, the enemies in EnemiesNoSync are sorted by name, for diff-friendly output.
+    #[arg(long)]
+    sort_enemies: bool,
+    /// With --sort-enemies, the controls of each enemy are sorted by name too.
+    #[arg(long, requires = "sort_enemies")]
+    sort_keys: bool,
+    /// CD2 version the source was already migrated to, so that the translation rules introduced
+    /// up to that version are not applied again. If not specified, the Version field of the
+    /// source is used if it is a CD2 version known to the translation data.
+    #[arg(long, value_name = "VERSION")]
+    since_version: Option<String>,
+    /// CD2 version the converted file is meant for. Only the translation rules meant for that
+    /// version are applied, which also decides where the pawn stats that moved between versions
+    /// go. If not specified, the latest version is targeted.
+    #[arg(long, value_name = "VERSION", env = "CD2IFIER_TARGET_VERSION")]
+    target_version: Option<String>,
+    /// If specified, existing target files will be overwritten, and sources that look like they
+    /// are already CD2 files are converted anyway.
+    #[arg(short, long)]
+    force: bool,
+    /// If specified, each converted file is written back to its source path, keeping its name.
+    /// The original is backed up as with --backup, unless --no-backup is given.
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["target_file", "output_dir", "output_suffix", "output_extension", "gzip"]
+    )]
+    in_place: bool,
+    /// With --in-place, the originals are not backed up.
+    #[arg(long, requires = "in_place", conflicts_with = "backup")]
+    no_backup: bool,
+    /// If specified, a target file about to be overwritten is first copied to the same path
+    /// with .bak appended. Converting a file in place, with -o set to the source, keeps the
+    /// original that way.
+    #[arg(long)]
+    backup: bool,
+    /// If specified, the converted file is written gzip-compressed, and .gz is appended to the
+    /// target file name when it is derived from the source. Target files ending in .gz are always
+    /// compressed.
+    #[arg(long)]
+    gzip: bool,
+    /// If specified, the conversion will run but nothing will be written. The conversion report
+    /// and the target file that would have been written are printed instead.
+    #[arg(long)]
+    dry_run: bool,
+    /// If specified, the files are only checked for problems and nothing is written. Fails if
+    /// any field, pawn stat or enemy base can't be translated or the resupply fields are malformed.
+    #[arg(long)]
+    validate_only: bool,
+    /// If specified, nothing is written and the distinct fields, pawn stats and enemy controls
+    /// that can't be translated are printed instead, with their number of occurrences across
+    /// all the source files.
+    #[arg(long, conflicts_with_all = ["validate_only", "dry_run"])]
+    list_unsupported: bool,
+    /// If specified, nothing is written nor logged and only the numbers of files that convert
+    /// cleanly, with warnings and that fail are printed, like for a badge. The run fails if any
+    /// file does.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "validate_only",
+            "dry_run",
+            "list_unsupported",
+            "porcelain",
+            "stats",
+            "diff_against"
+        ]
+    )]
+    summary_only: bool,
+    /// If specified, the conversion fails when a field or pawn stat can't be translated. The
+    /// whole file is processed first so that all the problems are reported at once.
+    #[arg(long, env = "CD2IFIER_STRICT")]
+    strict: bool,
+    /// If specified, the converted file is checked against the shape CD2 expects, like the
+    /// module names, the types of the values and the hazard. Problems are warned about, or make
+    /// the conversion fail with --strict.
+    #[arg(long, env = "CD2IFIER_CHECK_SCHEMA")]
+    check_schema: bool,
+    /// If specified, the numbers of the converted file are checked against the bounds in the
+    /// SANITY_BOUNDS of the translation data, past which the game may crash. Values out of range
+    /// are warned about, or make the conversion fail with --strict.
+    #[arg(long, env = "CD2IFIER_SANITY_CHECK")]
+    sanity_check: bool,
+    /// If specified, the single entry arrays of the converted file are collapsed into plain
+    /// values, like a pool of one enemy into its name. Ambiguous ones are warned about and kept.
+    #[arg(long)]
+    flatten: bool,
+    /// Value forced at a JSON pointer of the converted file once it is built, like
+    /// /DifficultySetting/BaseHazard=4. The value is JSON, so strings are quoted. Can be repeated.
+    #[arg(long = "set", value_name = "POINTER=VALUE", value_parser = Override::from_str)]
+    overrides: Vec<Override>,
+    /// If specified, how long each file took to convert and the numbers of enemies, fields and
+    /// pawn stats translated in it are logged at the end, along with the totals.
+    #[arg(long)]
+    stats: bool,
+    /// If specified, the list of changes made by the conversion is printed to stderr: fields
+    /// moved into modules, values replaced or inverted, fields added and fields dropped.
+    #[arg(long)]
+    show_diff: bool,
+    /// Path to a previously converted file that the converted file is compared with, when
+    /// converting a single file. The differences are printed to stderr and make the run fail.
+    #[arg(long, value_name = "PATH")]
+    diff_against: Option<String>,
+    /// Comma-separated sections to convert, out of resupply, top, enemies and escort. The other
+    /// sections are copied as they are.
+    #[arg(long, value_name = "SECTIONS", value_delimiter = ',', value_parser = Section::from_str, conflicts_with = "skip")]
+    only: Option<Vec<Section>>,
+    /// Comma-separated sections copied as they are instead of being converted, out of resupply,
+    /// top, enemies and escort.
+    #[arg(long, value_name = "SECTIONS", value_delimiter = ',', value_parser = Section::from_str)]
+    skip: Vec<Section>,
+    /// If specified, the "$include": "path.json" directives of the source files are resolved
+    /// before converting them, merging the objects of the included files, whose paths are
+    /// relative to the including file, into the objects holding the directives.
+    #[arg(long)]
+    resolve_includes: bool,
+    /// If specified, each top module of a converted file, like DifficultySetting, is written to
+    /// its own file in a directory named after the target file, inside --output-dir. The target
+    /// file is then an index including them, which --resolve-includes merges back.
+    #[arg(
+        long,
+        requires = "output_dir",
+        conflicts_with_all = ["gzip", "diff_against", "reverse"]
+    )]
+    split: bool,
+    /// If specified, CD2 files are converted back to CD1. Target file names get .cd1 instead of
+    /// .cd2.
+    #[arg(long)]
+    reverse: bool,
+    /// If specified, CD2 files are only reformatted, with the standard indentation and module
+    /// order, without translating anything. Multiline descriptions are kept.
+    #[arg(long, conflicts_with = "reverse")]
+    reformat: bool,
+    /// If specified, a single line JSON summary of each converted file is printed to stdout, with
+    /// the keys source, target, status, warnings and, if the conversion failed, error.
+    #[arg(long)]
+    porcelain: bool,
+    /// If specified and the source is a directory, its subdirectories will be converted too.
+    #[arg(short, long)]
+    recursive: bool,
+    /// Maximum number of files converted at the same time when the source is a directory. If not
+    /// specified, one per CPU.
+    #[arg(
+        short,
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u16).range(1..),
+        env = "CD2IFIER_JOBS"
+    )]
+    jobs: Option<u16>,
+    /// Path to a custom CD1 to CD2 translation data file. If not specified, the data built into
+    /// the script is used.
+    #[arg(long, value_name = "PATH", env = "CD2IFIER_TRANSLATION_DATA")]
+    translation_data: Option<String>,
+    /// Maximum nesting depth of objects and arrays accepted in a source file.
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+    /// Maximum size in bytes accepted for a source file.
+    #[arg(long, value_name = "N")]
+    max_bytes: Option<usize>,
+    /// Number of seconds to wait for a source or translation data given as a URL.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 30,
+        env = "CD2IFIER_TIMEOUT"
+    )]
+    timeout: u64,
+    /// Format of the logs written to stderr.
+    #[arg(long, value_enum, default_value_t = LogFormat::Text, env = "CD2IFIER_LOG_FORMAT")]
+    log_format: LogFormat,
+    /// Most verbose level of the logs written to stderr.
+    #[arg(
+        long,
+        value_enum,
+        value_name = "LEVEL",
+        default_value_t = LogLevel::Info,
+        env = "CD2IFIER_LOG_LEVEL"
+    )]
+    log_level: LogLevel,
+    /// If specified, only the errors and the final summary are logged.
+    #[arg(short, long, conflicts_with = "log_level")]
+    quiet: bool,
+    /// Path where a JSON report of everything the conversion skipped or changed will be written.
+    #[arg(long, value_name = "PATH", env = "CD2IFIER_REPORT_FILE")]
+    report_file: Option<String>,
+    /// Command run after each converted file is written, with the path of the file as its last
+    /// argument, like a signer or an uploader. It is run by the shell, and the file counts as
+    /// failed if the command does.
+    #[arg(long, value_name = "CMD")]
+    post_hook: Option<String>,
+    /// Prints the completion script for the given shell and exits.
+    #[arg(long, value_name = "SHELL", hide = true)]
+    generate_completions: Option<Shell>,
+}
+
+/// Name of the file setting project defaults for the flags, looked up from the working
+/// directory upward.
+const CONFIG_FILE: &str = "cd2ifier.toml";
+
+/// Flags of the config file holding paths, which are relative to the directory of the config
+/// file rather than to the working directory.
+const CONFIG_PATHS: [&str; 5] = [
+    "output",
+    "output-dir",
+    "translation-data",
+    "report-file",
+    "diff-against",
+];
+
+/// Parses the command line. The flags it doesn't give are taken from their CD2IFIER_ environment
+/// variable, if any. The ones still missing, nor any flag conflicting with them, are taken from
+/// the closest config file, if there is one, and then from the built-in defaults.
+fn parse_args() -> Args {
+    let cli_args: Vec<OsString> = std::env::args_os().collect();
+    let config_path = std::env::current_dir().ok().and_then(|dir| {
+        dir.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+    });
+    let Some(config_path) = config_path else {
+        return Args::parse_from(cli_args);
+    };
+    let config_args = config_args(&config_path, &cli_args).unwrap_or_else(|e| {
+        Args::command()
+            .error(
+                ErrorKind::InvalidValue,
+                format!("in the config file {}: {e:#}", config_path.display()),
+            )
+            .exit()
+    });
+    // The config flags go first, so that the sources given after -- stay last:
+    let mut args = cli_args;
+    let program = args.remove(0);
+    Args::parse_from([program].into_iter().chain(config_args).chain(args))
+}
+
+/// Turns the entries of a config file into command line flags, leaving out the ones given on
+/// the command line or by the environment. A true boolean stands for a flag without value, a
+/// false one for nothing.
+fn config_args(config_path: &Path, cli_args: &[OsString]) -> Result<Vec<OsString>> {
+    let config: toml::Table = fs::read_to_string(config_path)?.parse()?;
+    let mut command = Args::command();
+    command.build();
+    let cli_matches = command
+        .clone()
+        .try_get_matches_from(cli_args)
+        .unwrap_or_else(|e| e.exit());
+    let given: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| {
+            matches!(
+                cli_matches.value_source(arg.get_id().as_str()),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        })
+        .collect();
+    // Conflicts are declared on one side only:
+    let conflicts = |arg: &Arg, other: &Arg| {
+        arg.get_id() == other.get_id()
+            || command.get_arg_conflicts_with(arg).contains(&other)
+            || command.get_arg_conflicts_with(other).contains(&arg)
+    };
+    let mut args = Vec::new();
+    for (key, value) in &config {
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(key) && !arg.is_hide_set())
+        else {
+            bail!("[{key}] is not a cd2ifier flag");
+        };
+        if given.iter().any(|other| conflicts(arg, other)) {
+            continue;
+        }
+        let value = match value {
+            toml::Value::Boolean(true) => {
+                args.push(format!("--{key}").into());
+                continue;
             }
-            self
-        }
-    }
-    fn build_resupply_module(self) -> Self {
-        // Resupply module. Copy the cost if StartingNitra is 0 or missing, otherwise add
-        // the corresponding nitra mutator
-
-        fn compute_supply_vector(starting_nitra: f64, original_cost: f64) -> Vec<f64> {
-            if starting_nitra <= original_cost {
-                vec![original_cost - starting_nitra, original_cost]
-            } else {
-                std::iter::repeat(0.0)
-                    .take((starting_nitra / original_cost) as usize)
-                    .chain(vec![
-                        original_cost - starting_nitra % original_cost,
-                        original_cost,
-                    ])
-                    .collect()
+            toml::Value::Boolean(false) => continue,
+            toml::Value::String(path) if CONFIG_PATHS.contains(&key.as_str()) => {
+                if path == STDIO || is_url(path) {
+                    path.into()
+                } else {
+                    config_path.with_file_name(path).into_os_string()
+                }
             }
-        }
+            toml::Value::String(value) => value.into(),
+            toml::Value::Integer(value) => value.to_string().into(),
+            toml::Value::Float(value) => value.to_string().into(),
+            _ => bail!("[{key}] must be a boolean, a number or a string"),
+        };
+        let mut flag = OsString::from(format!("--{key}="));
+        flag.push(value);
+        args.push(flag);
+    }
+    Ok(args)
+}
 
-        let mut new = self.new.clone();
-        let original_resupply_cost: f64 =
-            if !self.original["ResupplyCost"].is_null() && self.original["ResupplyCost"] != 80 {
-                self.original["ResupplyCost"].as_f64().unwrap()
-            } else {
-                80.00
-            };
-        if self.original["StartingNitra"].is_null() || self.original["StartingNitra"] == 0 {
-            new["Resupply"]["Cost"] = original_resupply_cost.into();
+fn file_to_string(path: &str, timeout: u64) -> Result<String> {
+    if path == STDIO {
+        io::read_to_string(io::stdin()).with_context(|| "Something went wrong when reading stdin")
+    } else if is_url(path) {
+        fetch_url(path, timeout)
+            .with_context(|| format!("Something went wrong when fetching {path}"))
+    } else {
+        let bytes = fs::read(path)
+            .with_context(|| format!("Something went wrong when reading the file {}", path))?;
+        // Compressed files, like the ones written with --gzip, are told by their magic number
+        // rather than by their name:
+        let bytes = if bytes.starts_with(&GZIP_MAGIC) {
+            decompress(&bytes).with_context(|| format!("Could not decompress the file {path}"))?
         } else {
-            new["Resupply"]["Cost"] = object! {
-                "Mutate": "ByResuppliesCalled",
-                "Values": compute_supply_vector(
-                    self.original["StartingNitra"].as_f64().unwrap(),
-                    original_resupply_cost
-                )
+            bytes
+        };
+        String::from_utf8(bytes).with_context(|| format!("{path} is not a UTF-8 text file"))
+    }
+}
+
+fn is_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Downloads the body of a URL, giving up after `timeout` seconds.
+fn fetch_url(url: &str, timeout: u64) -> Result<String, ureq::Error> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(timeout)))
+        .build()
+        .into();
+    agent.get(url).call()?.body_mut().read_to_string()
+}
+
+fn load_translation_data(path: Option<&str>, timeout: u64) -> Result<JsonValue> {
+    if let Some(path) = path {
+        Ok(cd2ifier::parse_translation_data(&file_to_string(
+            path, timeout,
+        )?)?)
+    } else {
+        Ok(cd2ifier::default_translation_data()?)
+    }
+}
+
+/// Derives the target file name from the source one when no target is given, inserting the
+/// suffix (.cd2, or .cd1 when converting back, unless `--output-suffix` is given) before the
+/// last extension: foo.json becomes foo.cd2.json and foo.bar.json becomes foo.bar.cd2.json.
+/// `--output-extension` replaces the extension. With `--gzip`, .gz is appended. The .gz of a
+/// compressed source is left out first: foo.json.gz becomes foo.cd2.json.
+fn file_name<'a>(source: &'a str, target: Option<&'a str>, args: &Args) -> Result<Cow<'a, str>> {
+    if let Some(name) = target {
+        return Ok(Cow::Borrowed(name));
+    } else if args.in_place {
+        if source == STDIO || is_url(source) {
+            bail!("--in-place can only be used with source files, not with {source}");
+        }
+        return Ok(Cow::Borrowed(source));
+    } else if source == STDIO {
+        // Reading from stdin, the result goes to stdout:
+        return Ok(Cow::Borrowed(STDIO));
+    }
+    // The name of a URL comes from its last path segment, without the query:
+    let path = if is_url(source) {
+        Path::new(source.split(['?', '#']).next().unwrap_or(source))
+    } else {
+        Path::new(source)
+    };
+    // A compressed source is named after the file it holds:
+    let path = path
+        .to_str()
+        .and_then(|path| path.strip_suffix(".gz"))
+        .map_or(path, Path::new);
+    let Some(file_name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+        bail!("Could not derive an output file name from {source}");
+    };
+    let suffix = output_suffix(args);
+    let extension = match &args.output_extension {
+        Some(extension) => Some(extension.trim_start_matches('.')),
+        None => path.extension().and_then(|ext| ext.to_str()),
+    };
+    let mut name = match extension {
+        Some(extension) if !extension.is_empty() => format!("{file_name}{suffix}.{extension}"),
+        _ => format!("{file_name}{suffix}"),
+    };
+    if args.gzip {
+        name.push_str(".gz");
+    }
+    Ok(Cow::Owned(name))
+}
+
+/// Converts the source file and writes the result to the target file. The entry for the report
+/// file is added to `report_entries` as soon as the conversion is done, before writing. With
+/// `--porcelain`, a summary line is printed once the file is done, whether it failed or not.
+fn convert_file(
+    source_file: &str,
+    target_file: &str,
+    args: &Args,
+    translation_data: &JsonValue,
+    report_entries: &mut Vec<JsonValue>,
+) -> Result<()> {
+    if args.porcelain && target_file == STDIO && !args.validate_only {
+        bail!("--porcelain can't be used when writing the converted file to stdout");
+    }
+    if args.post_hook.is_some() && target_file == STDIO && !args.validate_only {
+        bail!("--post-hook can't be used when writing the converted file to stdout");
+    }
+    if is_zip(source_file) {
+        let result = convert_zip(
+            source_file,
+            target_file,
+            args,
+            translation_data,
+            report_entries,
+        );
+        print_porcelain(source_file, target_file, None, &result, args);
+        return result;
+    }
+    let started = Instant::now();
+    let converted = file_to_string(source_file, args.timeout)
+        .and_then(|file_str| resolve_includes(source_file, file_str, args))
+        .and_then(|file_str| convert_str(&file_str, args, translation_data));
+    let (output, report) = match converted {
+        Ok(converted) => converted,
+        Err(e) => {
+            report_entries.push(timed(report_entry(source_file, Err(&e)), started));
+            let result = Err(e);
+            print_porcelain(source_file, target_file, None, &result, args);
+            return result;
+        }
+    };
+    report_entries.push(timed(report_entry(source_file, Ok(&report)), started));
+    if args.show_diff {
+        print_changelog(source_file, &report.changelog);
+    }
+
+    let mut result = write_converted(source_file, target_file, &output, &report, args);
+    if let (Ok(()), Some(reference)) = (&result, &args.diff_against) {
+        result = diff_against(reference, &output, args);
+    }
+    print_porcelain(source_file, target_file, Some(&report), &result, args);
+    result
+}
+
+/// Converts, reverses or reformats the text of a source file, depending on the arguments.
+fn convert_str(
+    file_str: &str,
+    args: &Args,
+    translation_data: &JsonValue,
+) -> Result<(String, ConversionReport)> {
+    if args.reformat {
+        cd2ifier::reformat_str(file_str, &options(args))
+            .map(|output| (output, ConversionReport::default()))
+    } else if args.reverse {
+        cd2ifier::reverse_convert_str_with(file_str, translation_data, &options(args))
+    } else {
+        cd2ifier::convert_str_with(file_str, translation_data, &options(args))
+    }
+    .map_err(anyhow::Error::from)
+}
+
+/// Merges the files included by the source into it, with --resolve-includes.
+fn resolve_includes(source_file: &str, file_str: String, args: &Args) -> Result<String> {
+    if !args.resolve_includes {
+        return Ok(file_str);
+    }
+    if is_url(source_file) {
+        bail!("--resolve-includes can't be used with a source given as a URL");
+    }
+    Ok(cd2ifier::resolve_includes_str(
+        &file_str,
+        Path::new(source_file),
+        |path| fs::read_to_string(path),
+    )?)
+}
+
+/// Whether the source is a zip archive of source files.
+fn is_zip(source_file: &str) -> bool {
+    source_file != STDIO
+        && !is_url(source_file)
+        && Path::new(source_file)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+/// Converts the JSON files of a zip archive in memory. The converted files are written into a
+/// new archive at the target, under the derived names but keeping their paths inside the
+/// archive, along with the other files of the archive as they are. With `--output-dir`, they
+/// are written as separate files inside it instead. A failing file is logged and skipped as in a
+/// batch, but the archive is then not written.
+fn convert_zip(
+    source_file: &str,
+    target_file: &str,
+    args: &Args,
+    translation_data: &JsonValue,
+    report_entries: &mut Vec<JsonValue>,
+) -> Result<()> {
+    if args.diff_against.is_some() {
+        bail!("--diff-against can't be used with a zip archive");
+    } else if args.resolve_includes {
+        bail!("--resolve-includes can't be used with a zip archive");
+    } else if args.gzip && args.output_dir.is_none() {
+        bail!("--gzip can't be used when writing a zip archive");
+    }
+    let archive = fs::read(source_file)
+        .with_context(|| format!("Something went wrong when reading the file {source_file}"))?;
+    let entries = archive::read(&archive)
+        .with_context(|| format!("Could not read the zip archive {source_file}"))?;
+    let converted_ending = converted_ending(args);
+    let mut converted = Vec::new();
+    let (mut total, mut failed) = (0, Vec::new());
+    for entry in entries {
+        let is_source = entry.name.ends_with(".json")
+            && !converted_ending
+                .as_deref()
+                .is_some_and(|ending| entry.name.ends_with(ending));
+        if !is_source {
+            converted.push(entry);
+            continue;
+        }
+        total += 1;
+        let entry_source = format!("{source_file}/{}", entry.name);
+        let _span = span!(Level::INFO, "file", source = %entry_source).entered();
+        match convert_zip_entry(&entry_source, entry, args, translation_data, report_entries) {
+            Ok(Some(entry)) => converted.push(entry),
+            Ok(None) => (),
+            Err(e) => {
+                event!(Level::ERROR, "{:#}", e);
+                event!(Level::ERROR, "Skipping file: {}", entry_source);
+                failed.push(entry_source);
             }
         }
-        DiffContainer {
-            new,
-            original: self.original,
-        }
-    }
-    fn build_enemies_module(self, translation_data: &JsonValue) -> Self {
-        // Enemies module, copy as-is but fix the old pawn stats and remove deprecated fields:
-        let mut new = self.new.clone();
-        if !self.original["EnemyDescriptors"].is_null() {
-            new["EnemiesNoSync"] = self.original["EnemyDescriptors"].clone();
-            // Fix pawn stats:
-            for (enemy, controls) in new["EnemiesNoSync"].entries_mut() {
-                if !controls["PawnStats"].is_null() {
-                    let pawn_stats = controls.remove("PawnStats");
-                    translate_pawn_stats(
-                        controls,
-                        &pawn_stats,
-                        &translation_data["PAWN_STATS"],
-                        enemy,
-                    );
-                }
-                // Remove deprecated fields:
-                for (field, _) in self.original["EnemyDescriptors"][enemy].entries() {
-                    if !translation_data["VALID_ENEMY_CONTROLS"].contains(field)
-                        && field != "PawnStats"
-                    {
-                        event!(
-                            Level::INFO,
-                            "Deprecated or mistyped enemy control: [{field}] in [{enemy}]. Skipping."
-                        );
-                        controls.remove(field);
-                    }
+    }
+    event!(
+        target: SUMMARY,
+        Level::INFO,
+        "{} converted, {} failed in {source_file}",
+        total - failed.len(),
+        failed.len()
+    );
+    if !failed.is_empty() {
+        event!(target: SUMMARY, Level::INFO, "Failed files: [{}]", failed.join(", "));
+        bail!(
+            "{} of {total} files of {source_file} couldn't be converted",
+            failed.len()
+        );
+    }
+    if args.output_dir.is_some() || args.validate_only || args.list_unsupported || args.summary_only
+    {
+        return Ok(());
+    }
+    let archive =
+        archive::write(&converted).with_context(|| WriteFailure(target_file.to_string()))?;
+    write_target(source_file, target_file, &archive, &[], args)
+}
+
+/// Converts a JSON file of a zip archive. With `--output-dir`, the result is written there and
+/// None is returned, otherwise it is returned as the entry of the converted archive, unless
+/// nothing has to be written.
+fn convert_zip_entry(
+    entry_source: &str,
+    entry: archive::Entry,
+    args: &Args,
+    translation_data: &JsonValue,
+    report_entries: &mut Vec<JsonValue>,
+) -> Result<Option<archive::Entry>> {
+    let started = Instant::now();
+    let converted = String::from_utf8(entry.content)
+        .with_context(|| format!("{entry_source} is not a UTF-8 text file"))
+        .and_then(|file_str| convert_str(&file_str, args, translation_data));
+    let (output, report) = match converted {
+        Ok(converted) => converted,
+        Err(e) => {
+            report_entries.push(timed(report_entry(entry_source, Err(&e)), started));
+            return Err(e);
+        }
+    };
+    report_entries.push(timed(report_entry(entry_source, Ok(&report)), started));
+    if args.show_diff {
+        print_changelog(entry_source, &report.changelog);
+    }
+    // The derived name keeps the path of the file inside the archive:
+    let name = Path::new(&entry.name).with_file_name(&*file_name(&entry.name, None, args)?);
+    if let Some(output_dir) = &args.output_dir {
+        let target = Path::new(output_dir).join(name);
+        write_converted(
+            entry_source,
+            &target.to_string_lossy(),
+            &output,
+            &report,
+            args,
+        )?;
+        return Ok(None);
+    }
+    if !check_converted(&report, args)? {
+        return Ok(None);
+    }
+    let name = name.to_string_lossy().replace('\\', "/");
+    if args.dry_run {
+        let mut entry = report_entry(entry_source, Ok(&report));
+        entry["target"] = name.as_str().into();
+        println!("{}", json::stringify_pretty(entry, 4));
+    }
+    Ok(Some(archive::Entry {
+        name,
+        content: output.into_bytes(),
+    }))
+}
+
+/// Checks the report of a conversion that went through against strict mode or the validation,
+/// telling whether the result is to be written, which is not the case for a validation,
+/// `--list-unsupported` or `--summary-only`. A source that is already a CD2 file is refused
+/// unless --force is given.
+fn check_converted(report: &ConversionReport, args: &Args) -> Result<bool> {
+    if report.already_cd2 && !args.force {
+        bail!("The source looks like it is already a CD2 file, pass --force to convert it anyway");
+    }
+    if args.list_unsupported {
+        // The untranslated parts are listed from the report entries once every file is done:
+        return Ok(false);
+    }
+    if args.validate_only {
+        let issues = report.validation_issues();
+        if !issues.is_empty() {
+            bail!(
+                "Validation failed: {} issues found: {}",
+                issues.len(),
+                issues.join(", ")
+            );
+        }
+        event!(target: SUMMARY, Level::INFO, "Validation passed: {report}");
+        return Ok(false);
+    }
+    if args.strict {
+        let issues = report.strict_issues();
+        if !issues.is_empty() {
+            bail!(
+                "Strict mode: {} issues found: {}",
+                issues.len(),
+                issues.join(", ")
+            );
+        }
+    }
+    Ok(!args.summary_only)
+}
+
+/// Writes the result of a conversion that went through, unless strict mode rejects it or it is
+/// a dry run or a validation.
+fn write_converted(
+    source_file: &str,
+    target_file: &str,
+    output: &str,
+    report: &ConversionReport,
+    args: &Args,
+) -> Result<()> {
+    if !check_converted(report, args)? {
+        return Ok(());
+    }
+    if args.dry_run {
+        // Report what would have been done instead of writing: the conversion report and the
+        // target file.
+        let mut entry = report_entry(source_file, Ok(report));
+        entry["target"] = target_file.into();
+        println!("{}", json::stringify_pretty(entry, 4));
+    } else {
+        event!(Level::DEBUG, "Conversion report: {report}");
+    }
+    if args.split {
+        let module_dir = Path::new(target_file)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let split = cd2ifier::split_str(output, &module_dir, &options(args))?;
+        let modules: Vec<(PathBuf, &[u8])> = split
+            .modules
+            .iter()
+            .map(|(path, module)| {
+                let target_dir = Path::new(target_file).parent().unwrap_or(Path::new(""));
+                (target_dir.join(path), module.as_bytes())
+            })
+            .collect();
+        return write_target(
+            source_file,
+            target_file,
+            split.index.as_bytes(),
+            &modules,
+            args,
+        );
+    }
+    write_target(source_file, target_file, output.as_bytes(), &[], args)
+}
+
+/// Writes a converted file, or an archive of them, to the target file and runs the post hook on
+/// it. The module files of a split file are written first, the same way. A dry run only fails
+/// if the target couldn't be written without --force.
+fn write_target(
+    source_file: &str,
+    target_file: &str,
+    output: &[u8],
+    modules: &[(PathBuf, &[u8])],
+    args: &Args,
+) -> Result<()> {
+    // A derived name without suffix nor output directory can be the source itself, which is only
+    // overwritten when asked for with -o:
+    if args.target_file.is_none() && !args.in_place && is_same_file(source_file, target_file) {
+        bail!(
+            "The target file {target_file} derived from the source is the source itself, pass \
+            --output-dir, --output-suffix or -o to choose where to write it"
+        );
+    }
+    if args.dry_run {
+        if target_file != STDIO && !args.force && !args.in_place && Path::new(target_file).exists()
+        {
+            bail!("Target file {target_file} already exists, pass --force to overwrite it");
+        }
+        event!(
+            target: SUMMARY,
+            Level::INFO,
+            "Dry run finished, the result would be saved on file: {}",
+            target_file
+        );
+        return Ok(());
+    }
+    let gzip = args.gzip || target_file.ends_with(".gz");
+    // Single file runs on a terminal ask before overwriting, batches would mix the prompts:
+    let prompt = io::stdin().is_terminal() && io::stderr().is_terminal() && !is_batch(args);
+    // Converting in place is asking for the source to be overwritten:
+    let force = args.force || args.in_place;
+    let backup = args.backup || (args.in_place && !args.no_backup);
+    for (module_file, module) in modules {
+        write_output(
+            &module_file.to_string_lossy(),
+            module,
+            force,
+            prompt,
+            backup,
+            false,
+        )?;
+    }
+    write_output(target_file, output, force, prompt, backup, gzip)?;
+
+    if target_file == STDIO {
+        event!(target: SUMMARY, Level::INFO, "Conversion finished, written to stdout");
+    } else {
+        event!(
+            target: SUMMARY,
+            Level::INFO,
+            "Conversion finished, saving on file: {}",
+            target_file
+        );
+    }
+    if let Some(hook) = &args.post_hook {
+        run_post_hook(hook, target_file)?;
+    }
+
+    Ok(())
+}
+
+/// Runs the `--post-hook` command through the shell for a written file, passed as its last
+/// argument. The output of the command is logged, and it failing is an error.
+fn run_post_hook(hook: &str, target_file: &str) -> Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = process::Command::new("cmd");
+        command.arg("/C").arg(hook);
+        command
+    } else {
+        // The file goes in $1 so that the shell doesn't interpret its name:
+        let mut command = process::Command::new("sh");
+        command
+            .arg("-c")
+            .arg(format!("{hook} \"$1\""))
+            .arg("cd2ifier");
+        command
+    };
+    let output = command
+        .arg(target_file)
+        .stdin(process::Stdio::null())
+        .output()
+        .with_context(|| format!("Could not run the post hook [{hook}]"))?;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        event!(Level::INFO, "Post hook: {line}");
+    }
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        event!(Level::WARN, "Post hook: {line}");
+    }
+    if !output.status.success() {
+        bail!(
+            "The post hook [{hook}] failed for {target_file} with {}",
+            output.status
+        );
+    }
+    event!(Level::INFO, "Post hook [{hook}] done for [{target_file}].");
+    Ok(())
+}
+
+/// Prints the single line JSON summary of a file for `--porcelain`. Its keys are stable so that
+/// scripts can rely on them.
+fn print_porcelain(
+    source_file: &str,
+    target_file: &str,
+    report: Option<&ConversionReport>,
+    result: &Result<()>,
+    args: &Args,
+) {
+    if !args.porcelain {
+        return;
+    }
+    let mut line = object! {
+        "source": source_file,
+        "target": target_file,
+        "status": if result.is_ok() { "ok" } else { "failed" },
+        "warnings": report.map(ConversionReport::warning_count),
+    };
+    if let Err(e) = result {
+        line["error"] = format!("{:#}", e).into();
+    }
+    println!("{}", json::stringify(line));
+}
+
+/// Writes the converted file, gzip-compressed if `gzip` is set. Unless `force` is set, an
+/// existing target file is not overwritten: the new file is linked into place, which fails if
+/// the target exists, so the check and the creation happen at once. With `prompt`, the user is
+/// asked whether to overwrite it.
+/// With `backup`, a file that gets overwritten is backed up first. The content is written with
+/// [`write_atomically`], so that an interrupted run doesn't leave a truncated file. The missing
+/// directories of the target are created first.
+fn write_output(
+    target_file: &str,
+    output: &[u8],
+    force: bool,
+    prompt: bool,
+    backup: bool,
+    gzip: bool,
+) -> Result<()> {
+    let compressed;
+    let bytes = if gzip {
+        compressed = compress(output).with_context(|| WriteFailure(target_file.to_string()))?;
+        &compressed[..]
+    } else {
+        output
+    };
+    if target_file != STDIO {
+        // The missing directories of the target, like the subdirectories of the output
+        // directory, are created as needed:
+        if let Some(parent) = Path::new(target_file).parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Could not create the directory {}", parent.display()))
+                .with_context(|| WriteFailure(target_file.to_string()))?;
+        }
+    }
+    if target_file == STDIO {
+        io::stdout().write_all(bytes)
+    } else if force {
+        if backup && Path::new(target_file).exists() {
+            back_up(target_file)?;
+        }
+        write_atomically(target_file, bytes, true)
+    } else {
+        match write_atomically(target_file, bytes, false) {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                if !prompt || !confirm_overwrite(target_file)? {
+                    bail!("Target file {target_file} already exists, pass --force to overwrite it")
                 }
-                // Elite detection;
-                if controls.has_key("Elite")
-                    && controls["Elite"] == true
-                    && !(translation_data["VANILLA_ELITE_ENEMIES"])
-                        .contains(controls["Base"].clone())
-                    && (translation_data["VANILLA_ELITE_ENEMIES"]).contains(enemy)
-                {
-                    event!(
-                        Level::INFO,
-                        "Non-vanilla elite enemy detected with base: [{}]",
-                        controls["Base"].clone()
-                    );
-                    controls["ForceEliteBase"] = enemy.into();
+                if backup {
+                    back_up(target_file)?;
                 }
+                write_atomically(target_file, bytes, true)
             }
+            written => written,
         }
-        DiffContainer {
-            new,
-            original: self.original,
-        }
-    }
-    fn build_top_modules(self, top_modules_map: &JsonValue) -> Self {
-        fn update_if_range_array(original_value: &JsonValue) -> JsonValue {
-            // This if block is trying to detect fields that have weights, since CD2 removes the
-            // "range" part of the bins:
-            if original_value.is_array()
-                && !original_value.is_empty()
-                && !original_value[0]["weight"].is_null()
-            {
-                original_value
-                    .members()
-                    .map(|arr| {
-                        object! {
-                            "weight": arr["weight"].clone(),
-                            "min": arr["range"]["min"].clone(),
-                            "max": arr["range"]["max"].clone()
-                        }
-                    })
-                    .collect::<Vec<JsonValue>>()
-                    .into()
-            } else {
-                original_value.clone()
-            }
-        }
+    }
+    .with_context(|| WriteFailure(target_file.to_string()))
+}
 
-        let mut new = self.new.clone();
-        for (original_key, original_value) in self.original.entries() {
-            if let Some(field_status) = top_modules_map[original_key].as_str() {
-                match FieldStatus::from_str(field_status).unwrap() {
-                    FieldStatus::Valid(top_module) => {
-                        new[top_module][original_key] = update_if_range_array(original_value);
-                    }
-                    FieldStatus::Deprecated => {
-                        event!(Level::INFO, "Deprecated field: [{original_key}]. Skipping.");
-                    }
-                    FieldStatus::Ignored => (),
-                }
-            } else {
-                event!(
-                    Level::WARN,
-                    "Unsupported field: [{original_key}]. Please open an issue."
-                );
+/// Writes a file through a temporary file in the same directory, renamed over the target once
+/// complete, so that the target is always either the previous file or the whole new one. The
+/// permissions of a replaced file are kept. Unless `replace` is set, an existing target is left
+/// alone and the write fails with [`io::ErrorKind::AlreadyExists`]. Where hard links are not
+/// supported, the target then briefly exists as an empty file before being replaced.
+fn write_atomically(target_file: &str, bytes: &[u8], replace: bool) -> io::Result<()> {
+    let target = Path::new(target_file);
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let temp_file = target.with_file_name(format!(".{file_name}.{}.tmp", process::id()));
+    let written = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_file)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(target) {
+            fs::set_permissions(&temp_file, metadata.permissions())?;
+        }
+        if replace {
+            return fs::rename(&temp_file, target);
+        }
+        // Unlike renaming, linking fails when the target exists:
+        match fs::hard_link(&temp_file, target) {
+            Ok(()) => {
+                let _ = fs::remove_file(&temp_file);
+                Ok(())
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Err(e),
+            // Filesystems without hard links, like FAT drives, get the name claimed with an
+            // empty file instead, which is then replaced:
+            Err(_) => {
+                OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(target)?;
+                fs::rename(&temp_file, target).inspect_err(|_| {
+                    let _ = fs::remove_file(target);
+                })
             }
         }
-        // Here we add the BaseHazard field, defaults to Hazard 5 for explicitness:
-        new["DifficultySetting"]["BaseHazard"] = "Hazard 5".into();
-        // Change the name of StationaryEnemies, which in CD2 changed name to StationaryPool:
-        let stationary_enemies = new["Pools"].remove("StationaryEnemies");
-        if !stationary_enemies.is_null() {
-            new["Pools"]["StationaryPool"] = stationary_enemies
-        }
-        DiffContainer {
-            new,
-            original: self.original,
-        }
-    }
-
-    fn write_to_file(
-        self,
-        target_file: &str,
-        dont_pretty_print: bool,
-        multilines: Option<String>,
-    ) -> Result<()> {
-        let append_multilines = |mlines| -> JsonValue {
-            let mut with_multilines = self.new.clone();
-            with_multilines["Description"] =
-                format! {"{}{}", self.new["Description"].as_str().unwrap(), mlines}.into();
-            with_multilines
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_file);
+    }
+    written
+}
+
+/// Copies a file about to be overwritten to the same path with .bak appended, replacing any
+/// previous backup.
+fn back_up(file: &str) -> Result<()> {
+    let backup = format!("{file}.bak");
+    fs::copy(file, &backup).with_context(|| format!("Could not back up {file} to {backup}"))?;
+    event!(Level::INFO, "Backed up [{file}] to [{backup}].");
+    Ok(())
+}
+
+/// Prints the changes made by the conversion to stderr, colored by kind when it is a terminal.
+fn print_changelog(source_file: &str, changelog: &[Change]) {
+    let color = io::stderr().is_terminal();
+    let mut lines = vec![format!("Changes made to {source_file}:")];
+    for change in changelog {
+        let ansi_color = match change {
+            Change::Added { .. } => "32",
+            Change::Dropped { .. } => "31",
+            _ => "33",
         };
+        lines.push(if color {
+            format!("  \x1b[{ansi_color}m{change}\x1b[0m")
+        } else {
+            format!("  {change}")
+        });
+    }
+    // Printed in one go, so that the changes of files converted in parallel don't mix:
+    let text = lines.join("\n");
+    match PROGRESS_BAR.get() {
+        Some(bar) => bar.suspend(|| eprintln!("{text}")),
+        None => eprintln!("{text}"),
+    }
+}
 
-        fs::write(
-            target_file,
-            if dont_pretty_print {
-                if let Some(mlines) = multilines {
-                    json::stringify(append_multilines(mlines))
-                } else {
-                    json::stringify(self.new)
-                }
-            } else if let Some(mlines) = multilines {
-                recover_multilines(&json::stringify_pretty(self.new, 4), &mlines)
-            } else {
-                json::stringify_pretty(self.new, 4)
-            },
-        )
-        .with_context(|| {
-            format!(
-                "There was a problem when writing to the final file {}",
-                target_file
-            )
-        })
+/// Compares the converted file with the reference file of `--diff-against`, printing the
+/// differences to stderr and failing if there are any.
+fn diff_against(reference_file: &str, output: &str, args: &Args) -> Result<()> {
+    let reference = file_to_string(reference_file, args.timeout)?;
+    let differences = cd2ifier::diff_str(&reference, output)
+        .with_context(|| format!("Could not compare with {reference_file}"))?;
+    if differences.is_empty() {
+        event!(
+            target: SUMMARY,
+            Level::INFO,
+            "The converted file is the same as {reference_file}"
+        );
+        return Ok(());
+    }
+    let color = io::stderr().is_terminal();
+    let mut lines = vec![format!("Differences with {reference_file}:")];
+    for difference in &differences {
+        let ansi_color = match difference {
+            Difference::Added { .. } => "32",
+            Difference::Removed { .. } => "31",
+            _ => "33",
+        };
+        lines.push(if color {
+            format!("  \x1b[{ansi_color}m{difference}\x1b[0m")
+        } else {
+            format!("  {difference}")
+        });
     }
+    eprintln!("{}", lines.join("\n"));
+    bail!(
+        "The converted file differs from {reference_file} in {} places",
+        differences.len()
+    );
 }
 
-enum FieldStatus {
-    Deprecated,
-    Ignored,
-    Valid(String),
+/// Asks on stderr whether to overwrite the target file, reading the answer from stdin. Anything
+/// but yes is a no.
+fn confirm_overwrite(target_file: &str) -> Result<bool> {
+    eprint!("Overwrite {target_file}? [y/N] ");
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .with_context(|| "Could not read the answer")?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
 }
 
-impl FromStr for FieldStatus {
-    type Err = ();
-    fn from_str(input: &str) -> Result<FieldStatus, Self::Err> {
-        match input {
-            "deprecated" => Ok(FieldStatus::Deprecated),
-            "ignore" => Ok(FieldStatus::Ignored),
-            _ => Ok(FieldStatus::Valid(input.to_string())),
+fn compress(output: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(output)?;
+    encoder.finish()
+}
+
+fn decompress(compressed: &[u8]) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+    GzDecoder::new(compressed).read_to_end(&mut output)?;
+    Ok(output)
+}
+
+/// Entry of the report file for one source file, holding its conversion report or, if the
+/// conversion failed, the error.
+fn report_entry(source_file: &str, report: Result<&ConversionReport, &anyhow::Error>) -> JsonValue {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let mut entry = object! {
+        "source": source_file,
+        "timestamp": timestamp,
+    };
+    match report {
+        Ok(report) => {
+            entry["status"] = "ok".into();
+            entry["warnings"] = report.warning_count().into();
+            entry["report"] = report.to_json();
+        }
+        Err(e) => {
+            entry["status"] = "failed".into();
+            entry["error"] = format!("{:#}", e).into();
         }
     }
+    entry
 }
 
-#[derive(Parser, Debug)]
-struct Args {
-    /// Path to the CD1 file to be converted.
-    source_file: String,
-    /// Path where the translated CD2 file will be written to. If not specified, the script will
-    /// append .cd2 to the original file name
-    target_file: Option<String>,
-    /// If specified, the JSON will be written in compact form.
-    #[arg(short, long)]
-    dont_pretty_print: bool,
+/// Numbers of files of a run that converted cleanly, with warnings and that failed, for
+/// `--summary-only`.
+#[derive(Default)]
+struct Summary {
+    clean: usize,
+    warned: usize,
+    failed: usize,
 }
 
-fn translate_pawn_stats(
-    controls: &mut JsonValue,
-    pawn_stats: &JsonValue,
-    pawn_stats_map: &JsonValue,
-    enemy: &str,
-) {
-    for (stat, value) in pawn_stats.entries() {
-        if !pawn_stats_map[stat].is_null() {
-            let new_module = pawn_stats_map[stat]["CD2_module"].as_str().unwrap();
-            let new_field = pawn_stats_map[stat]["CD2_field"].as_str().unwrap();
-            let new_value = if new_module != "Resistances" || stat == "PST_DamageResistance" {
-                value
-            } else {
-                &(1.0 - value.as_f64().unwrap()).into()
-            };
-            if new_module == "None" {
-                controls[new_field] = new_value.clone();
-            } else {
-                controls[new_module][new_field] = new_value.clone();
+impl Summary {
+    /// Counts a file from the result of its conversion and its report entries, of which a zip
+    /// archive has several.
+    fn add(&mut self, result: &Result<()>, report_entries: &[JsonValue]) {
+        if result.is_err() {
+            self.failed += 1;
+        } else if report_entries
+            .iter()
+            .any(|entry| entry["warnings"].as_usize().unwrap_or_default() > 0)
+        {
+            self.warned += 1;
+        } else {
+            self.clean += 1;
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} clean, {} with warnings, {} failing",
+            self.clean, self.warned, self.failed
+        )
+    }
+}
+
+/// Adds to a report entry the time it took to read and convert its file, in milliseconds.
+fn timed(mut entry: JsonValue, started: Instant) -> JsonValue {
+    entry["milliseconds"] = (started.elapsed().as_secs_f64() * 1000.0).into();
+    entry
+}
+
+/// Logs, for `--stats`, how long each file took to convert and what was translated in it,
+/// followed by the totals of the run.
+fn log_stats(report_entries: &[JsonValue], elapsed: Duration) {
+    let counts = |entry: &JsonValue| {
+        ["converted_enemies", "moved_fields", "remapped_pawn_stats"]
+            .map(|key| entry["report"][key].len())
+    };
+    let mut totals = [0; 3];
+    for entry in report_entries {
+        let milliseconds = entry["milliseconds"].as_f64().unwrap_or_default();
+        let source = &entry["source"];
+        if entry["status"] == "ok" {
+            let [enemies, fields, stats] = counts(entry);
+            event!(
+                Level::INFO,
+                "Stats: {milliseconds:.1} ms, {enemies} enemies, {fields} fields moved, \
+                {stats} pawn stats remapped in [{source}]"
+            );
+            for (total, count) in totals.iter_mut().zip([enemies, fields, stats]) {
+                *total += count;
             }
         } else {
             event!(
-                Level::WARN,
-                "Unsupported pawn stat: [{stat}] on enemy [{enemy}]. Please open an issue. Skipping."
+                Level::INFO,
+                "Stats: {milliseconds:.1} ms, failed [{source}]"
             );
         }
     }
+    let [enemies, fields, stats] = totals;
+    event!(
+        Level::INFO,
+        "Stats: {} files in {:.1} ms, {enemies} enemies, {fields} fields moved, \
+        {stats} pawn stats remapped",
+        report_entries.len(),
+        elapsed.as_secs_f64() * 1000.0
+    );
 }
 
-fn file_to_string(path: &str) -> Result<String> {
-    fs::read_to_string(path)
-        .with_context(|| format!("Something went wrong when reading the file {}", path))
+/// Prints, for `--list-unsupported`, the fields, pawn stats and enemy controls the conversions
+/// couldn't translate, each with its number of occurrences, the most common first.
+fn print_unsupported(report_entries: &[JsonValue]) {
+    let kinds = [
+        ("Unsupported fields", "unsupported_fields"),
+        ("Unsupported pawn stats", "unsupported_pawn_stats"),
+        (
+            "Deprecated or unknown enemy controls",
+            "deprecated_enemy_controls",
+        ),
+    ];
+    let mut found = false;
+    for (title, key) in kinds {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for item in report_entries
+            .iter()
+            .flat_map(|entry| entry["report"][key].members())
+        {
+            // Pawn stats and enemy controls are counted by name, whatever the enemy:
+            if let Some(name) = item.as_str().or_else(|| item["field"].as_str()) {
+                *counts.entry(name).or_default() += 1;
+            }
+        }
+        if counts.is_empty() {
+            continue;
+        }
+        found = true;
+        let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+        counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        println!("{title}:");
+        for (name, count) in counts {
+            println!("{count:>6}  {name}");
+        }
+    }
+    if !found {
+        println!("Everything could be translated.");
+    }
 }
 
-fn parse_json(file_str: &str) -> Result<JsonValue> {
-    json::parse(file_str)
-        .with_context(|| "The JSON parser couldn't parse the file. Is it a proper JSON?")
+fn write_report_file(
+    report_file: &str,
+    mut report_entries: Vec<JsonValue>,
+    batch: bool,
+) -> Result<()> {
+    // A single conversion gets a single entry, a batch conversion gets an array of them:
+    let report = if batch || report_entries.len() != 1 {
+        report_entries.into()
+    } else {
+        report_entries.remove(0)
+    };
+    fs::write(report_file, json::stringify_pretty(report, 4))
+        .with_context(|| WriteFailure(report_file.to_string()))?;
+    event!(
+        Level::INFO,
+        "Conversion report saved on file: {}",
+        report_file
+    );
+    Ok(())
 }
 
-fn parse_json_with_multilines(file_path: &str) -> Result<(JsonValue, Option<String>)> {
-    let original_file_str = file_to_string(file_path)?;
-    let (original_file_str, multilines) = maybe_extract_multilines(&original_file_str);
-    Ok((parse_json(&original_file_str)?, multilines))
+/// Whether two paths lead to the same existing file.
+fn is_same_file(a: &str, b: &str) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
 }
-/// This function checks for files that have multiline descriptions.
-/// It returns either the original file (if no multilines) or the
-/// original file with multilines removed, plus the multiline Strings as an Option
-fn maybe_extract_multilines(file_str: &str) -> (Cow<str>, Option<String>) {
-    let mut multiline_idx = (None, None);
-    for (line_num, line) in file_str.lines().enumerate() {
-        if multiline_idx.0.is_none() {
-            if line.trim().starts_with("\"Description\"") {
-                if line.trim_end().ends_with("\",") {
-                    // This file contains no multilines, we exit
-                    break;
-                } else {
-                    // This file contains a multiline description,
-                    // save the line_num in multiline_idx:
-                    multiline_idx.0 = Some(line_num + 1);
-                }
-            }
-        } else if line.trim().starts_with("\"") && line.trim() != "\"," {
-            multiline_idx.1 = Some(line_num - 1);
-            break;
-        }
-    }
-    if let (Some(start_multiline), Some(end_multiline)) = multiline_idx {
-        event!(Level::INFO, "Multiline description detected. Saving.");
-        let (multilines_removed, multilines): (Vec<_>, Vec<_>) =
-            file_str.lines().enumerate().partition_map(|(ii, line)| {
-                if ii == start_multiline - 1 {
-                    Either::Left(format!("{}{}", line, "\","))
-                } else if ii >= start_multiline && ii <= end_multiline {
-                    Either::Right(line)
-                } else {
-                    Either::Left(line.to_string())
-                }
-            });
-        (
-            Cow::Owned(multilines_removed.join("\n")),
-            Some(multilines.join("\n")),
-        )
+
+/// Suffix inserted in the target file names before the extension, see [`file_name`].
+fn output_suffix(args: &Args) -> Cow<'_, str> {
+    match &args.output_suffix {
+        Some(suffix) => Cow::Borrowed(suffix),
+        None => Cow::Owned(format!(".{}", target_format(args))),
+    }
+}
+
+/// End of the names of the files that were already converted with the current suffix and
+/// extension, like .cd2.json, which are not converted again. None without a suffix, when
+/// converted files can't be told apart from the sources.
+fn converted_ending(args: &Args) -> Option<String> {
+    let suffix = output_suffix(args);
+    if suffix.is_empty() {
+        return None;
+    }
+    match args
+        .output_extension
+        .as_deref()
+        .unwrap_or("json")
+        .trim_start_matches('.')
+    {
+        "" => Some(suffix.into_owned()),
+        extension => Some(format!("{suffix}.{extension}")),
+    }
+}
+
+/// Format of the converted files, cd2 or cd1 when converting back.
+fn target_format(args: &Args) -> &'static str {
+    if args.reverse {
+        "cd1"
     } else {
-        (Cow::Borrowed(file_str), None)
-    }
-}
-fn recover_multilines(json_string: &str, multilines: &str) -> String {
-    event!(Level::INFO, "Recovering multiline description.");
-    let mut recovered_file = Vec::new();
-    let mut description_found = false;
-    for line in json_string.lines() {
-        if !description_found {
-            if line.trim().starts_with("\"Description\"") {
-                description_found = true;
-                recovered_file.push(line.trim_end().trim_end_matches("\","));
-            } else {
-                recovered_file.push(line);
+        "cd2"
+    }
+}
+
+fn options(args: &Args) -> Options {
+    Options {
+        dont_pretty_print: args.dont_pretty_print,
+        indent: args.indent,
+        final_newline: !args.no_final_newline,
+        default_base_hazard: !args.no_base_hazard,
+        stamp: !args.no_stamp,
+        keep_unknown: args.keep_unknown,
+        keep_comments: args.keep_comments,
+        since_version: args.since_version.clone(),
+        target_version: args.target_version.clone(),
+        max_depth: args.max_depth,
+        max_bytes: args.max_bytes,
+        sort_enemies: args.sort_enemies,
+        sort_keys: args.sort_keys,
+        check_schema: args.check_schema,
+        sanity_check: args.sanity_check,
+        flatten: args.flatten,
+        overrides: args.overrides.clone(),
+        changelog: args.show_diff,
+        skip_sections: match &args.only {
+            Some(only) => Section::ALL
+                .into_iter()
+                .filter(|section| !only.contains(section))
+                .collect(),
+            None => args.skip.clone(),
+        },
+    }
+}
+
+/// Collects the JSON files inside a directory, walking its subdirectories if `recursive` is set.
+/// Symlinked directories are not followed to avoid loops, and files that were already converted
+/// (`*.cd2.json`, or `*.cd1.json` when converting back) are left out so that re-runs don't
+/// convert them again.
+fn collect_source_files(
+    dir: &Path,
+    recursive: bool,
+    converted_ending: Option<&str>,
+) -> Result<Vec<PathBuf>> {
+    let mut source_files = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| {
+        format!(
+            "Something went wrong when reading the directory {}",
+            dir.display()
+        )
+    })? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if recursive {
+                source_files.extend(collect_source_files(&path, recursive, converted_ending)?);
             }
-        } else {
-            recovered_file.push(multilines);
-            description_found = false;
-            recovered_file.push(line);
+        } else if path.is_file()
+            && path.extension().is_some_and(|ext| ext == "json")
+            && !converted_ending.is_some_and(|ending| path.to_string_lossy().ends_with(ending))
+        {
+            source_files.push(path);
         }
     }
-    recovered_file.join("\n")
+    source_files.sort();
+    Ok(source_files)
 }
 
-fn file_name<'a>(source: &'a str, target: Option<&'a str>) -> Cow<'a, str> {
-    if let Some(name) = target {
-        Cow::Borrowed(name)
-    } else {
-        let file_name = Path::new(source).file_stem().unwrap().to_str().unwrap();
-        let extension = Path::new(source).extension();
-        Cow::Owned(if let Some(extension) = extension {
-            format!("{}.cd2.{}", file_name, extension.to_str().unwrap())
-        } else {
-            format!("{file_name}.cd2")
-        })
+/// Target of a file converted in batch. Files found in a source directory go next to their
+/// source or, with `--output-dir`, at the same place relative to the output directory as the
+/// source is relative to the source directory. Files given by name go where a single file would.
+fn batch_target(
+    source_path: &Path,
+    source_dir: Option<&Path>,
+    file_name: &str,
+    args: &Args,
+) -> PathBuf {
+    if args.in_place {
+        return source_path.to_path_buf();
     }
+    let Some(output_dir) = &args.output_dir else {
+        return match source_dir {
+            Some(_) => source_path.with_file_name(file_name),
+            None => PathBuf::from(file_name),
+        };
+    };
+    let relative_dir = source_dir
+        .and_then(|source_dir| source_path.strip_prefix(source_dir).ok())
+        .and_then(Path::parent)
+        .unwrap_or(Path::new(""));
+    Path::new(output_dir).join(relative_dir).join(file_name)
 }
 
-fn run(args: &Args) -> Result<()> {
-    // Open the file containing CD1 to CD2 translation data:
-    let translation_data = parse_json(&file_to_string("src/cd2-modules.json")?)?;
-    let (cd1_json, multilines) = parse_json_with_multilines(&args.source_file)?;
-    let file_name = file_name(&args.source_file, args.target_file.as_deref());
-
-    DiffContainer {
-        new: json::JsonValue::new_object(),
-        original: &cd1_json,
-    }
-    .copy_field_if_exists("Name", "It is recommended to add a Name.".into())
-    .copy_field_if_exists(
-        "Description",
-        "It is recommended to add a Description.".into(),
-    )
-    .build_resupply_module()
-    .build_top_modules(&translation_data["TOP_MODULES"])
-    .build_enemies_module(&translation_data)
-    .copy_field_if_exists("EscortMule", None)
-    .write_to_file(&file_name, args.dont_pretty_print, multilines)?;
+/// Whether several files are converted, either because several sources or a directory were
+/// given.
+fn is_batch(args: &Args) -> bool {
+    args.source_files.len() > 1
+        || args
+            .source_files
+            .iter()
+            .any(|source| Path::new(source).is_dir())
+}
+
+/// Converts every source file, and every JSON file inside the source directories, writing each
+/// result next to its source or under the output directory.
+/// The files are converted in parallel, on `--jobs` threads if given. A failing file is logged
+/// and skipped so that it doesn't stop the rest of the batch.
+fn run_batch(
+    args: &Args,
+    translation_data: &JsonValue,
+    report_entries: &mut Vec<JsonValue>,
+    summary: &mut Summary,
+) -> Result<()> {
+    if args.target_file.is_some() {
+        bail!("A target file can't be specified when converting several files or a directory");
+    } else if args.source_files.iter().any(|source| source == STDIO) {
+        bail!("Stdin can't be read when converting several files or a directory");
+    }
+    // Each file along with the source directory it was found in, if any:
+    let mut source_files: Vec<(PathBuf, Option<&Path>)> = Vec::new();
+    for source in &args.source_files {
+        let source_path = Path::new(source);
+        if source_path.is_dir() {
+            source_files.extend(
+                collect_source_files(
+                    source_path,
+                    args.recursive,
+                    converted_ending(args).as_deref(),
+                )?
+                .into_iter()
+                .map(|path| (path, Some(source_path))),
+            );
+        } else {
+            source_files.push((source_path.to_path_buf(), None));
+        }
+    }
 
+    // Two sources with the same name would overwrite each other's result, if written:
+    if !args.validate_only && !args.list_unsupported && !args.summary_only {
+        let mut targets = HashSet::new();
+        for (source_path, source_dir) in &source_files {
+            if let Ok(file_name) = file_name(&source_path.to_string_lossy(), None, args) {
+                let target = batch_target(source_path, *source_dir, &file_name, args);
+                if !targets.insert(target.clone()) {
+                    bail!(
+                        "Several source files would be converted to {}",
+                        target.display()
+                    );
+                }
+            }
+        }
+    }
+
+    // The progress bar is only drawn on a terminal, piped stderr gets the logs alone:
+    if io::stderr().is_terminal() {
+        let bar = ProgressBar::new(source_files.len() as u64).with_style(
+            ProgressStyle::with_template("{bar:40} {pos}/{len} files")
+                .with_context(|| "Could not set up the progress bar")?,
+        );
+        let _ = PROGRESS_BAR.set(bar);
+    }
+
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(jobs) = args.jobs {
+        pool = pool.num_threads(jobs.into());
+    }
+    // Each file gets its own report entries, which are put back in order once all are done:
+    let results: Vec<(String, Result<()>, Vec<JsonValue>)> = pool
+        .build()
+        .with_context(|| "Could not start the conversion threads")?
+        .install(|| {
+            source_files
+                .par_iter()
+                .map(|(source_path, source_dir)| {
+                    let source_file = source_path.to_string_lossy();
+                    // Prefix the logs of each file with its name, since they are interleaved:
+                    let _span = span!(Level::INFO, "file", source = %source_file).entered();
+                    let mut entries = Vec::new();
+                    let result = file_name(&source_file, None, args).and_then(|file_name| {
+                        convert_file(
+                            &source_file,
+                            &batch_target(source_path, *source_dir, &file_name, args)
+                                .to_string_lossy(),
+                            args,
+                            translation_data,
+                            &mut entries,
+                        )
+                    });
+                    if let Err(e) = &result {
+                        event!(Level::ERROR, "{:#}", e);
+                        event!(Level::ERROR, "Skipping file: {}", source_file);
+                    }
+                    if let Some(bar) = PROGRESS_BAR.get() {
+                        bar.inc(1);
+                    }
+                    (source_file.into_owned(), result, entries)
+                })
+                .collect()
+        });
+
+    if let Some(bar) = PROGRESS_BAR.get() {
+        bar.finish_and_clear();
+    }
+    let mut failed = Vec::new();
+    for (source_file, result, entries) in results {
+        summary.add(&result, &entries);
+        report_entries.extend(entries);
+        if result.is_err() {
+            failed.push(source_file);
+        }
+    }
     event!(
+        target: SUMMARY,
         Level::INFO,
-        "Conversion finished, saving on file: {}",
-        file_name
+        "{} converted, {} failed",
+        source_files.len() - failed.len(),
+        failed.len()
     );
-
+    if !failed.is_empty() {
+        event!(target: SUMMARY, Level::INFO, "Failed files: [{}]", failed.join(", "));
+        bail!(
+            "{} of {} files couldn't be converted",
+            failed.len(),
+            source_files.len()
+        );
+    }
     Ok(())
 }
 
-fn main() {
-    tracing_subscriber::fmt()
-        .without_time()
-        .with_ansi(std::io::stdout().is_terminal())
-        .init();
-    let args: Args = Args::parse();
+fn run(args: &Args) -> Result<()> {
+    let started = Instant::now();
+    // Load the CD1 to CD2 translation data, either the built-in one or the one given by the user:
+    let translation_data = load_translation_data(args.translation_data.as_deref(), args.timeout)?;
+    let batch = is_batch(args);
+    if let Some(output_dir) = &args.output_dir {
+        if args.source_files.iter().any(|source| source == STDIO) {
+            bail!("--output-dir can't be used when reading from stdin");
+        } else if Path::new(output_dir).exists() && !Path::new(output_dir).is_dir() {
+            bail!("The output directory {output_dir} exists and is not a directory");
+        }
+    }
+    if batch && args.diff_against.is_some() {
+        bail!("--diff-against can only be used when converting a single file");
+    }
+    let mut report_entries = Vec::new();
+    let mut summary = Summary::default();
+    let result = if batch {
+        run_batch(args, &translation_data, &mut report_entries, &mut summary)
+    } else {
+        let source_file = &args.source_files[0];
+        let _span = span!(Level::INFO, "file", source = %source_file).entered();
+        let result =
+            file_name(source_file, args.target_file.as_deref(), args).and_then(|file_name| {
+                let file_name = match &args.output_dir {
+                    Some(output_dir) => Cow::Owned(
+                        Path::new(output_dir)
+                            .join(&*file_name)
+                            .to_string_lossy()
+                            .into_owned(),
+                    ),
+                    None => file_name,
+                };
+                convert_file(
+                    source_file,
+                    &file_name,
+                    args,
+                    &translation_data,
+                    &mut report_entries,
+                )
+            });
+        summary.add(&result, &report_entries);
+        result
+    };
+    if args.stats {
+        log_stats(&report_entries, started.elapsed());
+    }
+    if args.summary_only {
+        println!("{summary}");
+    }
+    if args.list_unsupported && !report_entries.is_empty() {
+        print_unsupported(&report_entries);
+    }
+    // The report is saved even if the conversion failed, as long as some file was processed:
+    if let Some(report_file) = &args.report_file {
+        if !report_entries.is_empty() {
+            write_report_file(report_file, report_entries, batch)?;
+        }
+    }
+    result
+}
+
+/// Exit code for a failed conversion: 2 if a file couldn't be read, 3 if it wasn't valid JSON,
+/// 4 if the converted file couldn't be written and 1 for anything else.
+fn exit_code(error: &anyhow::Error) -> ExitCode {
+    if error.downcast_ref::<WriteFailure>().is_some() {
+        ExitCode::from(4)
+    } else {
+        match error.downcast_ref::<ConversionError>() {
+            Some(ConversionError::Parse(_)) => ExitCode::from(3),
+            Some(ConversionError::Io(_)) => ExitCode::from(2),
+            _ if error.downcast_ref::<io::Error>().is_some() => ExitCode::from(2),
+            _ => ExitCode::FAILURE,
+        }
+    }
+}
+
+/// Progress of a batch conversion, when stderr is a terminal.
+static PROGRESS_BAR: OnceLock<ProgressBar> = OnceLock::new();
+
+/// Writes the logs to stderr, hiding the progress bar while doing so, so that the logs don't
+/// get mixed with it.
+struct LogWriter;
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match PROGRESS_BAR.get() {
+            Some(bar) => bar.suspend(|| io::stderr().write(buf)),
+            None => io::stderr().write(buf),
+        }
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+fn main() -> ExitCode {
+    let args = parse_args();
+    if let Some(shell) = args.generate_completions {
+        clap_complete::generate(shell, &mut Args::command(), "cd2ifier", &mut io::stdout());
+        return ExitCode::SUCCESS;
+    }
+    // Spans are always kept, so that the events shown still tell which file they are about:
+    let max_level = if args.quiet {
+        Level::ERROR
+    } else {
+        args.log_level.into()
+    };
+    let (quiet, summary_only) = (args.quiet, args.summary_only);
+    let filter = dynamic_filter_fn(move |metadata, context| {
+        if summary_only {
+            // Only the errors stopping the whole run, which are outside of any file:
+            return metadata.is_span()
+                || (*metadata.level() == Level::ERROR && context.lookup_current().is_none());
+        }
+        metadata.is_span()
+            || *metadata.level() <= max_level
+            || (quiet && metadata.target() == SUMMARY)
+    });
+    // Diagnostics go to stderr so that stdout stays clean when the result is written there:
+    let layer = tracing_subscriber::fmt::layer().with_writer(|| LogWriter);
+    match args.log_format {
+        LogFormat::Text => tracing_subscriber::registry()
+            .with(
+                layer
+                    .without_time()
+                    .with_ansi(io::stderr().is_terminal())
+                    .with_filter(filter),
+            )
+            .init(),
+        // The fields of the spans, like the source file and the enemy, are kept as attributes
+        // of each event:
+        LogFormat::Json => tracing_subscriber::registry()
+            .with(
+                layer
+                    .json()
+                    .with_current_span(false)
+                    .with_span_list(true)
+                    .with_filter(filter),
+            )
+            .init(),
+    }
     if let Err(e) = run(&args) {
         event!(Level::ERROR, "{:#}", e);
         event!(Level::ERROR, "Conversion unfinished. Exiting.");
+        return exit_code(&e);
     }
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,216 @@
+//! Difficulty files commonly have string fields, usually the Description, whose value spans
+//! several lines. That is not proper JSON, so these fields are extracted from the file before
+//! parsing it and recovered as they were when writing the converted file.
+
+use crate::comments::Scan;
+use json::JsonValue;
+use std::borrow::Cow;
+use tracing::{event, Level};
+
+/// A top-level string field whose value spans several lines.
+#[derive(Debug, Clone)]
+struct Multiline {
+    key: String,
+    /// The lines following the one with the key, as written in the file and without the quote
+    /// closing the string.
+    lines: Vec<String>,
+}
+
+/// The multiline fields found in a file, in the order they were found, along with the line
+/// ending used by the file so it can be kept in the converted one.
+#[derive(Debug, Clone)]
+pub(crate) struct Multilines {
+    fields: Vec<Multiline>,
+    line_ending: &'static str,
+}
+
+impl Default for Multilines {
+    fn default() -> Self {
+        Multilines {
+            fields: Vec::new(),
+            line_ending: "\n",
+        }
+    }
+}
+
+/// Returns the key of a line like `"Key": "value`, if it has that shape.
+fn string_field_key(line: &str) -> Option<&str> {
+    let rest = line.trim_start().strip_prefix('"')?;
+    let key_end = rest.find('"')?;
+    let value = rest[key_end + 1..].trim_start().strip_prefix(':')?;
+    value
+        .trim_start()
+        .starts_with('"')
+        .then_some(&rest[..key_end])
+}
+
+/// Checks the file for top-level string fields spanning several lines. Returns the file with
+/// those fields cut down to their first line, so it can be parsed, together with the lines
+/// that were removed. The removed lines are left empty in the file, keeping the line numbers.
+/// Strings spanning several lines deeper in the file, like a Description nested in a module,
+/// can't be recovered where the conversion moves them, so they are joined into a single line
+/// with escaped line endings instead, which keeps their value.
+pub(crate) fn extract(file_str: &str) -> (Cow<'_, str>, Multilines) {
+    // Files written on Windows use CRLF line endings. `lines` takes care of stripping the CR,
+    // so the line ending is only needed to write the lines back:
+    let line_ending = if file_str.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut lines: Vec<String> = Vec::new();
+    let mut multilines = Vec::new();
+    // The multiline field being read, with the index of the line holding its key and whether
+    // it is nested:
+    let mut current: Option<(usize, Multiline, bool)> = None;
+    let mut joined_nested = false;
+    // Comments are skipped, so that quotes and braces in them don't throw the scan off:
+    let mut scan = Scan::default();
+    for line in file_str.lines() {
+        if let Some((key_line, mut multiline, nested)) = current.take() {
+            if let Some(pos) = scan.line(line) {
+                multiline.lines.push(line[..pos].to_string());
+                if nested {
+                    let escaped_line_ending = line_ending.escape_default().to_string();
+                    for line in &multiline.lines {
+                        lines[key_line].push_str(&escaped_line_ending);
+                        lines[key_line].push_str(line);
+                    }
+                    joined_nested = true;
+                } else {
+                    multilines.push(multiline);
+                }
+                // Close the string on the line with the key, keeping whatever followed the
+                // closing quote, usually a comma:
+                lines[key_line].push_str(&line[pos..]);
+            } else {
+                multiline.lines.push(line.to_string());
+                current = Some((key_line, multiline, nested));
+            }
+            // An empty line takes the place of the removed one, so that the parse errors point
+            // to the right line:
+            lines.push(String::new());
+            continue;
+        }
+        let top_level = scan.depth == 1 && !scan.in_string;
+        let starts_in_string = scan.in_string;
+        scan.line(line);
+        let still_in_string = scan.in_string;
+        if top_level && still_in_string {
+            if let Some(key) = string_field_key(line) {
+                event!(Level::INFO, "Multiline field detected: [{key}]. Saving.");
+                current = Some((
+                    lines.len(),
+                    Multiline {
+                        key: key.to_string(),
+                        lines: Vec::new(),
+                    },
+                    false,
+                ));
+                lines.push(line.to_string());
+                continue;
+            }
+        } else if !starts_in_string && still_in_string && scan.depth > 1 {
+            event!(
+                Level::INFO,
+                "Nested multiline string detected on line {}. Joining it into a single line.",
+                lines.len() + 1
+            );
+            current = Some((
+                lines.len(),
+                Multiline {
+                    key: string_field_key(line).unwrap_or_default().to_string(),
+                    lines: Vec::new(),
+                },
+                true,
+            ));
+            lines.push(line.to_string());
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    if (multilines.is_empty() && !joined_nested) || current.is_some() {
+        // Either there is nothing to extract or a string is never closed, in which case the
+        // file is left as it is for the parser to report.
+        (
+            Cow::Borrowed(file_str),
+            Multilines {
+                fields: Vec::new(),
+                line_ending,
+            },
+        )
+    } else {
+        (
+            Cow::Owned(lines.join(line_ending)),
+            Multilines {
+                fields: multilines,
+                line_ending,
+            },
+        )
+    }
+}
+
+impl Multilines {
+    /// Line ending used by the source file.
+    pub(crate) fn line_ending(&self) -> &'static str {
+        self.line_ending
+    }
+
+    /// Puts the multiline fields back into a pretty-printed JSON, whose top-level fields are
+    /// indented by `indent` spaces. Each field is written as it was in the source file, and the
+    /// lines are joined with the line ending of the source file.
+    pub(crate) fn recover(&self, json_string: &str, indent: usize) -> String {
+        if self.fields.is_empty() && self.line_ending == "\n" {
+            return json_string.to_string();
+        }
+        let mut recovered_file = Vec::new();
+        for line in json_string.lines() {
+            let top_level = line.len() - line.trim_start().len() == indent;
+            let multiline = self.fields.iter().find(|multiline| {
+                top_level && string_field_key(line) == Some(multiline.key.as_str())
+            });
+            if let Some(multiline) = multiline {
+                event!(
+                    Level::INFO,
+                    "Recovering multiline field: [{}].",
+                    multiline.key
+                );
+                let comma = if line.ends_with(',') { "," } else { "" };
+                recovered_file.push(
+                    line.trim_end_matches(',')
+                        .strip_suffix('"')
+                        .unwrap_or(line)
+                        .to_string(),
+                );
+                recovered_file.extend(multiline.lines.iter().cloned());
+                if let Some(last) = recovered_file.last_mut() {
+                    last.push('"');
+                    last.push_str(comma);
+                }
+            } else {
+                recovered_file.push(line.to_string());
+            }
+        }
+        recovered_file.join(self.line_ending)
+    }
+
+    /// Appends the lines of each multiline field to its value in `json`, for outputs where the
+    /// fields can't be written over several lines, or objects other than the top level one.
+    pub(crate) fn append_to(&self, json: &mut JsonValue) {
+        for multiline in &self.fields {
+            let Some(first_line) = json[multiline.key.as_str()].as_str() else {
+                continue;
+            };
+            // The lines are still escaped as in the file, parse them as a JSON string to
+            // get their actual value:
+            let escaped = multiline
+                .lines
+                .join(&self.line_ending.escape_default().to_string());
+            let rest = json::parse(&format!("\"{escaped}\""))
+                .ok()
+                .and_then(|rest| rest.as_str().map(str::to_string))
+                .unwrap_or_else(|| multiline.lines.join(self.line_ending));
+            json[multiline.key.as_str()] = format!("{first_line}{}{rest}", self.line_ending).into();
+        }
+    }
+}
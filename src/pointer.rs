@@ -0,0 +1,123 @@
+//! Overrides of single fields of a converted document, addressed by JSON pointers (RFC 6901)
+//! like `/DifficultySetting/BaseHazard`, see [`Options::overrides`](crate::Options::overrides).
+
+use json::JsonValue;
+use std::fmt;
+use std::str::FromStr;
+
+/// A value forced at a path of the converted document, whatever the source holds there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    /// JSON pointer to the field, like `/DifficultySetting/BaseHazard`.
+    pub pointer: String,
+    pub value: JsonValue,
+}
+
+impl fmt::Display for Override {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.pointer, self.value.dump())
+    }
+}
+
+/// Parses `<pointer>=<value>`, the value being JSON, like `/DifficultySetting/BaseHazard=4`.
+impl FromStr for Override {
+    type Err = String;
+    fn from_str(input: &str) -> Result<Override, Self::Err> {
+        let Some((pointer, value)) = input.split_once('=') else {
+            return Err(format!(
+                "[{input}] is not of the form <json-pointer>=<value>"
+            ));
+        };
+        tokens(pointer)?;
+        let value = json::parse(value)
+            .map_err(|e| format!("the value of [{pointer}] is not valid JSON: {e}"))?;
+        Ok(Override {
+            pointer: pointer.to_string(),
+            value,
+        })
+    }
+}
+
+/// The unescaped reference tokens of a pointer. The root pointer, the empty one, is refused as
+/// it would replace the whole document.
+fn tokens(pointer: &str) -> Result<Vec<String>, String> {
+    let Some(path) = pointer.strip_prefix('/') else {
+        return Err(format!("the JSON pointer [{pointer}] doesn't start with /"));
+    };
+    path.split('/')
+        .map(|token| {
+            let mut unescaped = String::new();
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                if c != '~' {
+                    unescaped.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('0') => unescaped.push('~'),
+                    Some('1') => unescaped.push('/'),
+                    _ => {
+                        return Err(format!(
+                            "the JSON pointer [{pointer}] has a ~ not followed by 0 or 1"
+                        ))
+                    }
+                }
+            }
+            Ok(unescaped)
+        })
+        .collect()
+}
+
+/// The index of an array member addressed by a token, which may be the length of the array, or
+/// `-`, for appending to it.
+fn index(token: &str, len: usize) -> Option<usize> {
+    if token == "-" {
+        return Some(len);
+    }
+    // Leading zeros are not allowed:
+    if token.len() > 1 && token.starts_with('0') {
+        return None;
+    }
+    token.parse().ok().filter(|&index| index <= len)
+}
+
+/// Sets the field of the override in the document. The objects missing on the way are created,
+/// while going through anything else than an object or an existing array member fails.
+pub(crate) fn apply(document: &mut JsonValue, over: &Override) -> Result<(), String> {
+    let tokens = tokens(&over.pointer)?;
+    let (last, parents) = tokens.split_last().expect("split always yields a token");
+    let mut current = document;
+    for token in parents {
+        current = match current {
+            JsonValue::Object(object) => {
+                if object.get(token).is_none() {
+                    object.insert(token, JsonValue::new_object());
+                }
+                object.get_mut(token).expect("just inserted")
+            }
+            JsonValue::Array(members) => match index(token, members.len()) {
+                Some(index) if index < members.len() => &mut members[index],
+                _ => return Err(format!("[{token}] is not a member of its array")),
+            },
+            _ => {
+                return Err(format!(
+                    "[{token}] is inside a value that is neither an object nor an array"
+                ))
+            }
+        };
+    }
+    match current {
+        JsonValue::Object(object) => object.insert(last, over.value.clone()),
+        JsonValue::Array(members) => match index(last, members.len()) {
+            Some(index) if index < members.len() => members[index] = over.value.clone(),
+            Some(_) => members.push(over.value.clone()),
+            None => return Err(format!("[{last}] is not a member of its array")),
+        },
+        _ => {
+            return Err(format!(
+                "[{last}] is inside a value that is neither an object nor an array"
+            ))
+        }
+    }
+    Ok(())
+}
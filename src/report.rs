@@ -0,0 +1,284 @@
+use crate::Change;
+use json::{object, JsonValue};
+use std::fmt;
+
+/// A field of a specific enemy descriptor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnemyField {
+    pub enemy: String,
+    pub field: String,
+}
+
+impl EnemyField {
+    pub(crate) fn new(enemy: &str, field: &str) -> Self {
+        EnemyField {
+            enemy: enemy.to_string(),
+            field: field.to_string(),
+        }
+    }
+}
+
+/// A pawn stat that was translated into a CD2 enemy control. `target` is the path of the new
+/// control inside the enemy descriptor, like `Resistances.FireDamageMultiplier`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemappedPawnStat {
+    pub enemy: String,
+    pub stat: String,
+    pub target: String,
+}
+
+/// An elite enemy that got a `ForceEliteBase` because its base is not a vanilla elite.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EliteOverride {
+    pub enemy: String,
+    pub base: String,
+}
+
+/// An enemy whose `Base` is not in the known base enemies of the translation data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownBase {
+    pub enemy: String,
+    pub base: String,
+}
+
+/// Everything the conversion skipped or complained about, collected while the CD2 file is built.
+#[derive(Debug, Clone, Default)]
+pub struct ConversionReport {
+    /// Top-level fields that are deprecated in CD2 and were dropped. EscortMule fields are given
+    /// as `EscortMule.Field`.
+    pub deprecated_fields: Vec<String>,
+    /// Enemy controls that are deprecated or mistyped and were dropped.
+    pub deprecated_enemy_controls: Vec<EnemyField>,
+    /// Top-level fields the translation data doesn't know about, EscortMule fields included.
+    pub unsupported_fields: Vec<String>,
+    /// Pawn stats the translation data doesn't know about.
+    pub unsupported_pawn_stats: Vec<EnemyField>,
+    /// Fields that are not required but recommended, like Name and Description.
+    pub missing_recommended_fields: Vec<String>,
+    /// Resistance pawn stats outside of the 0 to 1 range.
+    pub out_of_range_resistances: Vec<EnemyField>,
+    /// Resistance pawn stats of a file that looks partly converted to CD2 already, which may
+    /// have been CD2 multipliers inverted a second time.
+    pub possibly_inverted_resistances: Vec<EnemyField>,
+    /// Problems found in the resupply fields, which were replaced by a usable cost.
+    pub resupply_warnings: Vec<String>,
+    /// Top-level fields that were moved into a CD2 module.
+    pub moved_fields: Vec<String>,
+    /// Enemies that were translated into `EnemiesNoSync`.
+    pub converted_enemies: Vec<String>,
+    /// Pawn stats that were translated to CD2 enemy controls.
+    pub remapped_pawn_stats: Vec<RemappedPawnStat>,
+    /// Pawn stats whose value was overwritten by another pawn stat of the same enemy going to
+    /// the same control.
+    pub overwritten_pawn_stats: Vec<RemappedPawnStat>,
+    /// Elite enemies that were given a `ForceEliteBase`.
+    pub elite_base_overrides: Vec<EliteOverride>,
+    /// Enemies that can be elite but have no vanilla elite enemy to be the elite version of,
+    /// so they never are.
+    pub elites_without_base: Vec<String>,
+    /// Enemies of an `EnemiesNoSync` built before the enemies were converted, which the
+    /// converted enemies of the same name replaced.
+    pub overwritten_enemies: Vec<String>,
+    /// Enemies whose base is unknown, likely because it is mistyped.
+    pub unknown_bases: Vec<UnknownBase>,
+    /// Single entry arrays that were not flattened because it would have been ambiguous, when
+    /// flattening.
+    pub unflattened_values: Vec<String>,
+    /// Parts of the converted file that don't have the shape CD2 expects, when checked.
+    pub schema_violations: Vec<String>,
+    /// Numbers of the converted file outside the bounds of the translation data, when checked.
+    pub out_of_bounds_values: Vec<String>,
+    /// Paths of the numbers of the converted file that are NaN or infinite.
+    pub non_finite_numbers: Vec<String>,
+    /// Keys written several times in an object of the source, by path, of which only the last
+    /// value was kept.
+    pub duplicate_keys: Vec<String>,
+    /// Whether the source looks like it is already a CD2 file, with CD2 modules but no
+    /// `EnemyDescriptors`, in which case the conversion is likely garbage.
+    pub already_cd2: bool,
+    /// Everything the conversion changed, when asked for.
+    pub changelog: Vec<Change>,
+}
+
+impl ConversionReport {
+    /// Number of issues that were reported as warnings.
+    pub fn warning_count(&self) -> usize {
+        self.unsupported_fields.len()
+            + self.unsupported_pawn_stats.len()
+            + self.missing_recommended_fields.len()
+            + self.out_of_range_resistances.len()
+            + self.possibly_inverted_resistances.len()
+            + self.overwritten_pawn_stats.len()
+            + self.resupply_warnings.len()
+            + self.unknown_bases.len()
+            + self.elites_without_base.len()
+            + self.overwritten_enemies.len()
+            + self.unflattened_values.len()
+            + self.schema_violations.len()
+            + self.out_of_bounds_values.len()
+            + self.non_finite_numbers.len()
+            + self.duplicate_keys.len()
+    }
+
+    /// Issues that make the conversion fail in strict mode: everything the translation data
+    /// couldn't translate, the enemies with an unknown base, the schema violations, the out of
+    /// range values, the NaN or infinite numbers and the duplicated keys.
+    pub fn strict_issues(&self) -> Vec<String> {
+        self.unsupported_fields
+            .iter()
+            .map(|field| format!("Unsupported field [{field}]"))
+            .chain(self.unsupported_pawn_stats.iter().map(|stat| {
+                format!(
+                    "Unsupported pawn stat [{}] on enemy [{}]",
+                    stat.field, stat.enemy
+                )
+            }))
+            .chain(self.unknown_bases.iter().map(|unknown| {
+                format!(
+                    "Unknown base enemy [{}] in [{}]",
+                    unknown.base, unknown.enemy
+                )
+            }))
+            .chain(
+                self.schema_violations
+                    .iter()
+                    .map(|violation| format!("Schema violation: {violation}")),
+            )
+            .chain(
+                self.out_of_bounds_values
+                    .iter()
+                    .map(|value| format!("Out of range value: {value}")),
+            )
+            .chain(
+                self.non_finite_numbers
+                    .iter()
+                    .map(|path| format!("NaN or infinite number at [{path}]")),
+            )
+            .chain(
+                self.duplicate_keys
+                    .iter()
+                    .map(|key| format!("Duplicated key [{key}]")),
+            )
+            .collect()
+    }
+
+    /// Issues that make the validation of a file fail: the strict mode issues and the malformed
+    /// resupply fields.
+    pub fn validation_issues(&self) -> Vec<String> {
+        let mut issues = self.strict_issues();
+        issues.extend(self.resupply_warnings.iter().cloned());
+        issues
+    }
+
+    /// The report as a JSON object, with one array per kind of issue.
+    pub fn to_json(&self) -> JsonValue {
+        fn enemy_fields(fields: &[EnemyField]) -> JsonValue {
+            fields
+                .iter()
+                .map(|f| object! {"enemy": f.enemy.clone(), "field": f.field.clone()})
+                .collect::<Vec<_>>()
+                .into()
+        }
+        fn remapped_pawn_stats(stats: &[RemappedPawnStat]) -> JsonValue {
+            stats
+                .iter()
+                .map(|r| {
+                    object! {
+                        "enemy": r.enemy.clone(),
+                        "stat": r.stat.clone(),
+                        "target": r.target.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .into()
+        }
+
+        object! {
+            "deprecated_fields": self.deprecated_fields.clone(),
+            "deprecated_enemy_controls": enemy_fields(&self.deprecated_enemy_controls),
+            "unsupported_fields": self.unsupported_fields.clone(),
+            "unsupported_pawn_stats": enemy_fields(&self.unsupported_pawn_stats),
+            "missing_recommended_fields": self.missing_recommended_fields.clone(),
+            "out_of_range_resistances": enemy_fields(&self.out_of_range_resistances),
+            "possibly_inverted_resistances": enemy_fields(&self.possibly_inverted_resistances),
+            "resupply_warnings": self.resupply_warnings.clone(),
+            "moved_fields": self.moved_fields.clone(),
+            "converted_enemies": self.converted_enemies.clone(),
+            "remapped_pawn_stats": remapped_pawn_stats(&self.remapped_pawn_stats),
+            "overwritten_pawn_stats": remapped_pawn_stats(&self.overwritten_pawn_stats),
+            "elite_base_overrides": self
+                .elite_base_overrides
+                .iter()
+                .map(|o| object! {"enemy": o.enemy.clone(), "base": o.base.clone()})
+                .collect::<Vec<_>>(),
+            "elites_without_base": self.elites_without_base.clone(),
+            "overwritten_enemies": self.overwritten_enemies.clone(),
+            "unknown_bases": self
+                .unknown_bases
+                .iter()
+                .map(|u| object! {"enemy": u.enemy.clone(), "base": u.base.clone()})
+                .collect::<Vec<_>>(),
+            "unflattened_values": self.unflattened_values.clone(),
+            "schema_violations": self.schema_violations.clone(),
+            "out_of_bounds_values": self.out_of_bounds_values.clone(),
+            "non_finite_numbers": self.non_finite_numbers.clone(),
+            "duplicate_keys": self.duplicate_keys.clone(),
+            "already_cd2": self.already_cd2,
+            "changelog": self
+                .changelog
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        }
+    }
+}
+
+impl fmt::Display for ConversionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Only the counters that aren't zero are listed, so that a clean file reads as such:
+        let counters = [
+            (self.deprecated_fields.len(), "deprecated fields"),
+            (
+                self.deprecated_enemy_controls.len(),
+                "deprecated enemy controls",
+            ),
+            (self.unsupported_fields.len(), "unsupported fields"),
+            (self.unsupported_pawn_stats.len(), "unsupported pawn stats"),
+            (
+                self.missing_recommended_fields.len(),
+                "missing recommended fields",
+            ),
+            (
+                self.out_of_range_resistances.len(),
+                "out of range resistances",
+            ),
+            (
+                self.possibly_inverted_resistances.len(),
+                "possibly inverted resistances",
+            ),
+            (self.overwritten_pawn_stats.len(), "overwritten pawn stats"),
+            (self.resupply_warnings.len(), "resupply warnings"),
+            (self.unknown_bases.len(), "unknown base enemies"),
+            (
+                self.elites_without_base.len(),
+                "elite enemies without elite base",
+            ),
+            (self.overwritten_enemies.len(), "overwritten enemies"),
+            (self.unflattened_values.len(), "ambiguous values to flatten"),
+            (self.schema_violations.len(), "schema violations"),
+            (self.out_of_bounds_values.len(), "out of range values"),
+            (self.non_finite_numbers.len(), "NaN or infinite numbers"),
+            (self.duplicate_keys.len(), "duplicated keys"),
+        ];
+        let counters: Vec<_> = counters
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, name)| format!("{count} {name}"))
+            .collect();
+        if counters.is_empty() {
+            write!(f, "no issues")
+        } else {
+            write!(f, "{}", counters.join(", "))
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Conversion of CD2 files back to the CD1 format, for difficulties that still have to run on
+//! CD1. It reads the translation data backwards, so everything the forward conversion creates
+//! is undone. CD2 constructs with no CD1 equivalent are warned about and skipped.
+
+use crate::{
+    is_inverted_resistance, is_weighted_bins, pawn_stat_targets, ConversionReport, EnemyField,
+    FieldStatus, RemappedPawnStat, Result, ENEMY_CONTROLS_MODULE, STAMP_KEY,
+};
+use json::{object, JsonValue};
+use std::str::FromStr;
+use tracing::{event, Level};
+
+struct ReverseContainer<'a> {
+    new: JsonValue,
+    original: &'a JsonValue,
+    report: ConversionReport,
+}
+
+impl<'a> ReverseContainer<'a> {
+    fn copy_field_if_exists(mut self, field: &str) -> Self {
+        if self.original.has_key(field) {
+            self.new[field] = self.original[field].clone();
+        }
+        self
+    }
+    fn build_resupply_fields(mut self) -> Self {
+        // Collapse the resupply cost back into ResupplyCost, and StartingNitra if there is a
+        // nitra mutator:
+        for (field, value) in self.original["Resupply"].entries() {
+            if field != "Cost" {
+                self.unsupported(format!("Resupply.{field}"));
+                continue;
+            }
+            if value.is_number() {
+                self.new["ResupplyCost"] = value.clone();
+            } else if is_weighted_bins(value) {
+                self.new["ResupplyCost"] = restore_range_array(value);
+            } else if value["Mutate"] == "ByResuppliesCalled" {
+                if let Some((starting_nitra, cost)) = collapse_supply_vector(&value["Values"]) {
+                    self.new["StartingNitra"] = starting_nitra.into();
+                    self.new["ResupplyCost"] = cost.into();
+                } else {
+                    let msg = format!(
+                        "Resupply cost mutator [{value}] doesn't match a StartingNitra. Skipping."
+                    );
+                    event!(Level::WARN, "{msg}");
+                    self.report.resupply_warnings.push(msg);
+                }
+            } else {
+                self.unsupported("Resupply.Cost".to_string());
+            }
+        }
+        self
+    }
+    fn build_top_fields(mut self, translation_data: &JsonValue) -> Self {
+        let top_modules_map = &translation_data["TOP_MODULES"];
+        let pool_renames = &translation_data["POOL_RENAMES"];
+        for (key, value) in self.original.entries() {
+            match key {
+                "Name" | "Description" | "Resupply" | "EnemiesNoSync" | "EscortMule" => continue,
+                // Only meaningful for the CD2 file:
+                STAMP_KEY => continue,
+                _ => (),
+            }
+            let is_module = top_modules_map.entries().any(|(_, module)| module == key);
+            if !is_module || !value.is_object() {
+                self.unsupported(key.to_string());
+                continue;
+            }
+            for (field, field_value) in value.entries() {
+                if key == "DifficultySetting" && field == "BaseHazard" {
+                    // The default hazard is added by the CD2 conversion, other hazards go back
+                    // to being numbers:
+                    if *field_value != translation_data["BASE_HAZARD_DEFAULT"]["value"] {
+                        self.new[field] = field_value
+                            .as_str()
+                            .and_then(|hazard| hazard.strip_prefix("Hazard "))
+                            .and_then(|hazard| hazard.parse::<f64>().ok())
+                            .map_or_else(|| field_value.clone(), JsonValue::from);
+                    }
+                    continue;
+                }
+                // Pools renamed in CD2 get their CD1 name back, before the new name is taken
+                // for a field of the module:
+                let old_name = pool_renames
+                    .entries()
+                    .find(|(_, rename)| rename["name"] == field)
+                    .map(|(old_name, _)| old_name);
+                if let (Some(old_name), "Pools") = (old_name, key) {
+                    self.new[old_name] = field_value.clone();
+                    continue;
+                }
+                let status = top_modules_map[field].as_str().map(FieldStatus::from_str);
+                if let Some(Ok(FieldStatus::Valid(module))) = status {
+                    if module == key {
+                        self.new[field] = restore_range_array(field_value);
+                        continue;
+                    }
+                }
+                self.unsupported(format!("{key}.{field}"));
+            }
+        }
+        self
+    }
+    fn build_escort_fields(mut self, escort_mule_map: &JsonValue) -> Self {
+        let escort_mule = &self.original["EscortMule"];
+        if !escort_mule.is_object() {
+            return self.copy_field_if_exists("EscortMule");
+        }
+        self.new["EscortMule"] = JsonValue::new_object();
+        for (field, value) in escort_mule.entries() {
+            // Several CD1 names may map to the same CD2 field, the last one is the current name:
+            match escort_mule_map
+                .entries()
+                .rev()
+                .find(|(_, new_field)| *new_field == field)
+            {
+                Some((old_field, _)) => {
+                    self.new["EscortMule"][old_field] = restore_range_array(value);
+                }
+                None => self.unsupported(format!("EscortMule.{field}")),
+            }
+        }
+        self
+    }
+    fn build_enemy_descriptors(mut self, translation_data: &JsonValue) -> Self {
+        if self.original["EnemiesNoSync"].is_null() {
+            return self;
+        }
+        self.new["EnemyDescriptors"] = self.original["EnemiesNoSync"].clone();
+        for (enemy, controls) in self.new["EnemyDescriptors"].entries_mut() {
+            // The forward conversion sets ForceEliteBase to the enemy itself:
+            if controls["ForceEliteBase"] == enemy {
+                controls.remove("ForceEliteBase");
+            }
+            let pawn_stats = restore_pawn_stats(
+                controls,
+                &translation_data["PAWN_STATS"],
+                enemy,
+                &mut self.report,
+            );
+            for (control, _) in self.original["EnemiesNoSync"][enemy].entries() {
+                if controls.has_key(control)
+                    && !translation_data["VALID_ENEMY_CONTROLS"].contains(control)
+                {
+                    event!(
+                        Level::WARN,
+                        "Enemy control [{control}] in [{enemy}] has no CD1 equivalent. Skipping."
+                    );
+                    controls.remove(control);
+                    self.report
+                        .unsupported_fields
+                        .push(format!("EnemiesNoSync.{enemy}.{control}"));
+                }
+            }
+            if !pawn_stats.is_empty() {
+                controls["PawnStats"] = pawn_stats;
+            }
+        }
+        self
+    }
+    fn unsupported(&mut self, field: String) {
+        event!(Level::WARN, "[{field}] has no CD1 equivalent. Skipping.");
+        self.report.unsupported_fields.push(field);
+    }
+}
+
+/// Inverse of the nitra mutator built by the forward conversion: the values are a run of free
+/// resupplies, a partial one and the cost itself. Returns the starting nitra and the cost.
+fn collapse_supply_vector(values: &JsonValue) -> Option<(f64, f64)> {
+    let values = values
+        .members()
+        .map(JsonValue::as_f64)
+        .collect::<Option<Vec<f64>>>()?;
+    let [free @ .., partial, cost] = values.as_slice() else {
+        return None;
+    };
+    if *cost <= 0.0 || !(0.0..=*cost).contains(partial) || free.iter().any(|&v| v != 0.0) {
+        return None;
+    }
+    Some((free.len() as f64 * cost + (cost - partial), *cost))
+}
+
+/// Puts back the "range" part of the weighted bins that CD2 removes.
+fn restore_range_array(value: &JsonValue) -> JsonValue {
+    if is_weighted_bins(value) {
+        value
+            .members()
+            .map(|bin| {
+                object! {
+                    "weight": bin["weight"].clone(),
+                    "range": {"min": bin["min"].clone(), "max": bin["max"].clone()}
+                }
+            })
+            .collect::<Vec<JsonValue>>()
+            .into()
+    } else {
+        value.clone()
+    }
+}
+
+/// Moves the controls that come from pawn stats out of `controls`, returning them as CD1 pawn
+/// stats. Resistance multipliers M become resistances 1 - M again, except for
+/// PST_DamageResistance.
+fn restore_pawn_stats(
+    controls: &mut JsonValue,
+    pawn_stats_map: &JsonValue,
+    enemy: &str,
+    report: &mut ConversionReport,
+) -> JsonValue {
+    let mut pawn_stats = JsonValue::new_object();
+    // A pawn stat whose target moved between CD2 versions can be found at any of them:
+    let targets = pawn_stats_map
+        .entries()
+        .flat_map(|(stat, mapping)| pawn_stat_targets(mapping).map(move |target| (stat, target)));
+    for (stat, target) in targets {
+        let (Some(module), Some(field)) =
+            (target["CD2_module"].as_str(), target["CD2_field"].as_str())
+        else {
+            continue;
+        };
+        let value = if module == ENEMY_CONTROLS_MODULE {
+            controls.remove(field)
+        } else {
+            let value = controls[module].remove(field);
+            if controls[module].is_empty() {
+                controls.remove(module);
+            }
+            value
+        };
+        if value.is_null() {
+            continue;
+        }
+        pawn_stats[stat] = if !is_inverted_resistance(module, stat) {
+            value
+        } else if let Some(multiplier) = value.as_f64() {
+            // Rounded so that 1 - 0.7 comes back as 0.3 and not 0.30000000000000004:
+            (((1.0 - multiplier) * 1e9).round() / 1e9).into()
+        } else {
+            event!(
+                Level::WARN,
+                "Resistance [{module}.{field}] on enemy [{enemy}] is not a number. Skipping."
+            );
+            report
+                .unsupported_pawn_stats
+                .push(EnemyField::new(enemy, field));
+            continue;
+        };
+        report.remapped_pawn_stats.push(RemappedPawnStat {
+            enemy: enemy.to_string(),
+            stat: stat.to_string(),
+            target: if module == ENEMY_CONTROLS_MODULE {
+                field.to_string()
+            } else {
+                format!("{module}.{field}")
+            },
+        });
+    }
+    pawn_stats
+}
+
+/// Converts a parsed CD2 document back to CD1 using the given translation data, returning it
+/// along with the report of everything that had no CD1 equivalent.
+pub fn reverse_convert(
+    cd2_json: &JsonValue,
+    translation_data: &JsonValue,
+) -> Result<(JsonValue, ConversionReport)> {
+    let converted = ReverseContainer {
+        new: JsonValue::new_object(),
+        original: cd2_json,
+        report: ConversionReport::default(),
+    }
+    .copy_field_if_exists("Name")
+    .copy_field_if_exists("Description")
+    .build_resupply_fields()
+    .build_top_fields(translation_data)
+    .build_enemy_descriptors(translation_data)
+    .build_escort_fields(&translation_data["ESCORT_MULE"]);
+    Ok((converted.new, converted.report))
+}
@@ -0,0 +1,149 @@
+//! Structural checks of a converted file against the shape CD2 expects, to catch outputs that
+//! wouldn't load in-game before they are written.
+
+use crate::{is_weighted_bins, STAMP_KEY, TOP_LEVEL_ORDER};
+use json::JsonValue;
+
+/// Modules holding numeric settings, which can also be given as per-player arrays, weighted
+/// bins or mutators.
+const NUMERIC_MODULES: [&str; 2] = ["DifficultySetting", "Caps"];
+
+/// Whether a value can stand for a number in CD2.
+fn is_numeric_value(value: &JsonValue) -> bool {
+    value.is_number()
+        || is_weighted_bins(value)
+        || (value.is_object() && value.has_key("Mutate"))
+        || (value.is_array() && value.members().all(JsonValue::is_number))
+}
+
+/// Checks a BaseHazard like `Hazard 5`.
+fn is_valid_hazard(value: &JsonValue) -> bool {
+    value
+        .as_str()
+        .and_then(|hazard| hazard.strip_prefix("Hazard "))
+        .and_then(|hazard| hazard.parse::<f64>().ok())
+        .is_some_and(|hazard| (1.0..=5.0).contains(&hazard))
+}
+
+/// Returns a description of every part of a CD2 document that doesn't have the shape CD2
+/// expects: unknown modules, values of the wrong type and out of range hazards.
+pub(crate) fn violations(cd2_json: &JsonValue) -> Vec<String> {
+    let mut violations = Vec::new();
+    for (key, value) in cd2_json.entries() {
+        match key {
+            "Name" | "Description" if !value.is_string() => {
+                violations.push(format!("[{key}] must be a string"));
+            }
+            "Name" | "Description" => (),
+            // Kept on purpose with --keep-unknown, for the user to migrate by hand:
+            "x-unconverted" => (),
+            STAMP_KEY => (),
+            _ if !TOP_LEVEL_ORDER.contains(&key) => {
+                violations.push(format!("[{key}] is not a CD2 module"));
+            }
+            _ if !value.is_object() => violations.push(format!("[{key}] must be an object")),
+            _ => (),
+        }
+    }
+    for module in NUMERIC_MODULES {
+        for (field, value) in cd2_json[module].entries() {
+            if module == "DifficultySetting" && field == "BaseHazard" {
+                if !is_valid_hazard(value) {
+                    violations.push(format!(
+                        "[{module}.{field}] must be a hazard from \"Hazard 1\" to \"Hazard 5\", \
+                        not [{value}]"
+                    ));
+                }
+            } else if !is_numeric_value(value) {
+                violations.push(format!(
+                    "[{module}.{field}] must be a number, not [{value}]"
+                ));
+            }
+        }
+    }
+    for (field, value) in cd2_json["Resupply"].entries() {
+        if !is_numeric_value(value) {
+            violations.push(format!(
+                "[Resupply.{field}] must be a number, not [{value}]"
+            ));
+        }
+    }
+    for (enemy, controls) in cd2_json["EnemiesNoSync"].entries() {
+        if !controls.is_object() {
+            violations.push(format!("[EnemiesNoSync.{enemy}] must be an object"));
+        } else if controls.has_key("Base") && !controls["Base"].is_string() {
+            violations.push(format!("[EnemiesNoSync.{enemy}.Base] must be a string"));
+        }
+    }
+    violations
+}
+
+/// The numbers a value stands for: itself, the values of a per-player array or the bounds of
+/// weighted bins. Mutators are left out, their values depend on the game.
+fn numbers(value: &JsonValue) -> Vec<f64> {
+    if is_weighted_bins(value) {
+        value
+            .members()
+            .flat_map(|bin| [&bin["min"], &bin["max"]])
+            .filter_map(JsonValue::as_f64)
+            .collect()
+    } else if value.is_array() {
+        value.members().filter_map(JsonValue::as_f64).collect()
+    } else {
+        value.as_f64().into_iter().collect()
+    }
+}
+
+/// Finds the values at a dotted path, where `*` stands for every key of an object.
+fn values_at<'a>(
+    value: &'a JsonValue,
+    path: &[&str],
+    prefix: String,
+    found: &mut Vec<(String, &'a JsonValue)>,
+) {
+    let Some((key, rest)) = path.split_first() else {
+        found.push((prefix, value));
+        return;
+    };
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        }
+    };
+    if *key == "*" {
+        for (key, child) in value.entries() {
+            values_at(child, rest, join(key), found);
+        }
+    } else if value.has_key(key) {
+        values_at(&value[*key], rest, join(key), found);
+    }
+}
+
+/// Returns a description of every number of a CD2 document outside the bounds of the
+/// translation data, which maps dotted paths to an optional `min` and `max`.
+pub(crate) fn out_of_bounds(cd2_json: &JsonValue, bounds: &JsonValue) -> Vec<String> {
+    let mut out_of_bounds = Vec::new();
+    for (path, bound) in bounds.entries() {
+        let mut found = Vec::new();
+        values_at(
+            cd2_json,
+            &path.split('.').collect::<Vec<_>>(),
+            String::new(),
+            &mut found,
+        );
+        for (field, value) in found {
+            for number in numbers(value) {
+                if let Some(min) = bound["min"].as_f64().filter(|&min| number < min) {
+                    out_of_bounds
+                        .push(format!("[{field}] is {number}, below the minimum of {min}"));
+                } else if let Some(max) = bound["max"].as_f64().filter(|&max| number > max) {
+                    out_of_bounds
+                        .push(format!("[{field}] is {number}, above the maximum of {max}"));
+                }
+            }
+        }
+    }
+    out_of_bounds
+}
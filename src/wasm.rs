@@ -0,0 +1,34 @@
+//! JavaScript bindings of the conversion, built with the `wasm` feature for running it in a
+//! browser, so that the files never leave the machine of the user. The errors are thrown as
+//! JavaScript errors holding the message the command line tool would log.
+
+use crate::{default_translation_data, Options};
+use json::object;
+use wasm_bindgen::prelude::*;
+
+/// Converts the text of a CD1 file to CD2 with the default options.
+#[wasm_bindgen(js_name = convertStr)]
+pub fn convert_str(input: &str) -> Result<String, JsError> {
+    crate::convert_str(input).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Converts the text of a CD1 file to CD2 with the default options, returning a JSON object
+/// with the converted file under `output` and the conversion report under `report`.
+#[wasm_bindgen(js_name = convertStrWithReport)]
+pub fn convert_str_with_report(input: &str) -> Result<String, JsError> {
+    let (output, report) = default_translation_data()
+        .and_then(|data| crate::convert_str_with(input, &data, &Options::default()))
+        .map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(json::stringify(
+        object! {"output": output, "report": report.to_json()},
+    ))
+}
+
+/// Converts the text of a CD2 file back to CD1 with the default options.
+#[wasm_bindgen(js_name = reverseConvertStr)]
+pub fn reverse_convert_str(input: &str) -> Result<String, JsError> {
+    default_translation_data()
+        .and_then(|data| crate::reverse_convert_str_with(input, &data, &Options::default()))
+        .map(|(output, _)| output)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
@@ -0,0 +1,55 @@
+//! Sources that are already CD2 files are flagged, and refused by the binary unless forced.
+
+use std::fs;
+use std::process::Command;
+
+const CD2: &str =
+    r#"{"Name": "CD2", "DifficultySetting": {"EnemyCountModifier": [1.0]}, "EnemiesNoSync": {}}"#;
+
+#[test]
+fn cd2_files_are_flagged_but_cd1_files_are_not() {
+    let (_, report) = cd2ifier::convert_str_with(
+        CD2,
+        &cd2ifier::default_translation_data().unwrap(),
+        &Default::default(),
+    )
+    .unwrap();
+    assert!(report.already_cd2);
+
+    let cd1 = r#"{"Name": "CD1", "MaxActiveEnemies": 60, "EnemyDescriptors": {}}"#;
+    let (_, report) = cd2ifier::convert_str_with(
+        cd1,
+        &cd2ifier::default_translation_data().unwrap(),
+        &Default::default(),
+    )
+    .unwrap();
+    assert!(!report.already_cd2);
+}
+
+#[test]
+fn cd2_files_are_only_converted_with_force() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-already-cd2-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let level = dir.join("level.json");
+    fs::write(&level, CD2).unwrap();
+    let target = dir.join("level.cd2.json");
+
+    let convert = |force: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_cd2ifier"));
+        command.arg(&level).arg("-o").arg(&target).arg("-q");
+        if force {
+            command.arg("--force");
+        }
+        command.output().unwrap()
+    };
+    let output = convert(false);
+    assert!(!output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already a CD2 file"));
+    assert!(!target.exists());
+
+    let output = convert(true);
+    assert!(output.status.success(), "{output:?}");
+    assert!(target.exists());
+    fs::remove_dir_all(dir).unwrap();
+}
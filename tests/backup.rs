@@ -0,0 +1,128 @@
+//! `--backup` and `--in-place` keep the files a conversion overwrites, which are replaced at once.
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn converting_in_place_keeps_the_original_as_a_backup() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-backup-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let level = dir.join("level.json");
+    let cd1 = r#"{"Name": "Backup", "Description": "Backup", "MaxActiveEnemies": 60}"#;
+    fs::write(&level, cd1).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+        .arg(&level)
+        .arg("-o")
+        .arg(&level)
+        .args(["--force", "--backup", "-q"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(fs::read_to_string(dir.join("level.json.bak")).unwrap(), cd1);
+    assert!(fs::read_to_string(&level).unwrap().contains("\"Caps\""));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn in_place_conversion_keeps_the_names_and_backs_up_the_originals() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-in-place-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    let cd1 = r#"{"Name": "In place", "Description": "In place", "MaxActiveEnemies": 60}"#;
+    for level in ["a.json", "sub/b.json"] {
+        fs::write(dir.join(level), cd1).unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+        .arg(&dir)
+        .args(["--in-place", "--recursive", "-q"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    for level in ["a.json", "sub/b.json"] {
+        assert!(fs::read_to_string(dir.join(level))
+            .unwrap()
+            .contains("\"Caps\""));
+        assert_eq!(
+            fs::read_to_string(dir.join(format!("{level}.bak"))).unwrap(),
+            cd1
+        );
+    }
+    assert!(!dir.join("a.cd2.json").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn overwriting_leaves_no_temporary_file() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-atomic-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let level = dir.join("level.json");
+    fs::write(&level, r#"{"Name": "Atomic", "Description": "Atomic"}"#).unwrap();
+    fs::write(dir.join("level.cd2.json"), "previous").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+        .arg(&level)
+        .args(["--force", "-q"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let mut files: Vec<String> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["level.cd2.json", "level.json"]);
+    assert!(fs::read_to_string(dir.join("level.cd2.json"))
+        .unwrap()
+        .contains("\"Atomic\""));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn an_existing_target_is_left_alone_without_force() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-no-clobber-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let level = dir.join("level.json");
+    fs::write(&level, r#"{"Name": "Kept", "Description": "Kept"}"#).unwrap();
+    let convert = || {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .arg(&level)
+            .arg("-q")
+            .current_dir(&dir)
+            .output()
+            .unwrap()
+    };
+    let files = || {
+        let mut files: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        files
+    };
+
+    let output = convert();
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(files(), ["level.cd2.json", "level.json"]);
+    assert!(fs::read_to_string(dir.join("level.cd2.json"))
+        .unwrap()
+        .contains("\"Kept\""));
+
+    fs::write(dir.join("level.cd2.json"), "previous").unwrap();
+    let output = convert();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("already exists, pass --force to overwrite it"));
+    assert_eq!(files(), ["level.cd2.json", "level.json"]);
+    assert_eq!(
+        fs::read_to_string(dir.join("level.cd2.json")).unwrap(),
+        "previous"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,37 @@
+//! The default `BaseHazard` added to the converted files that don't set one, unless
+//! `Options::default_base_hazard` is off.
+
+use cd2ifier::{convert_str_with, default_translation_data, Options};
+use json::JsonValue;
+
+fn convert(input: &str, default_base_hazard: bool) -> JsonValue {
+    let options = Options {
+        default_base_hazard,
+        ..Default::default()
+    };
+    let translation_data = default_translation_data().unwrap();
+    let (output, _) = convert_str_with(input, &translation_data, &options).unwrap();
+    json::parse(&output).unwrap()
+}
+
+#[test]
+fn the_default_base_hazard_is_added_unless_turned_off() {
+    let input = r#"{"Name": "No hazard", "EnemyCountModifier": [1.5]}"#;
+    let converted = convert(input, true);
+    assert_eq!(converted["DifficultySetting"]["BaseHazard"], "Hazard 5");
+
+    let converted = convert(input, false);
+    assert!(!converted["DifficultySetting"].has_key("BaseHazard"));
+    assert_eq!(
+        converted["DifficultySetting"]["EnemyCountModifier"],
+        json::array![1.5]
+    );
+}
+
+#[test]
+fn a_base_hazard_of_the_source_is_kept_either_way() {
+    for default_base_hazard in [true, false] {
+        let converted = convert(r#"{"BaseHazard": 3}"#, default_base_hazard);
+        assert_eq!(converted["DifficultySetting"]["BaseHazard"], "Hazard 3");
+    }
+}
@@ -0,0 +1,35 @@
+//! The CD1 cap fields, which go to the CD2 Caps module.
+
+use cd2ifier::{convert, default_translation_data};
+use json::object;
+
+#[test]
+fn cap_fields_go_to_the_caps_module_without_warnings() {
+    let cd1 = object! {
+        "Name": "Caps",
+        "Description": "Caps",
+        "MaxActiveCritters": 40,
+        "MaxActiveSwarmers": [60, 70, 80, 90],
+        "MaxActiveEnemies": 100
+    };
+    let (cd2, report) = convert(&cd1, &default_translation_data().unwrap()).unwrap();
+    assert_eq!(
+        cd2["Caps"],
+        object! {
+            "MaxActiveCritters": 40,
+            "MaxActiveSwarmers": [60, 70, 80, 90],
+            "MaxActiveEnemies": 100
+        }
+    );
+    assert!(report.unsupported_fields.is_empty());
+    assert_eq!(report.warning_count(), 0);
+}
+
+#[test]
+fn max_active_elites_is_dropped_as_deprecated() {
+    let cd1 = object! {"Name": "Caps", "Description": "Caps", "MaxActiveElites": 5};
+    let (cd2, report) = convert(&cd1, &default_translation_data().unwrap()).unwrap();
+    assert!(!cd2.has_key("Caps"));
+    assert_eq!(report.deprecated_fields, ["MaxActiveElites"]);
+    assert!(report.unsupported_fields.is_empty());
+}
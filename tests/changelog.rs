@@ -0,0 +1,47 @@
+//! The list of changes asked for with `Options::changelog`.
+
+use cd2ifier::{convert_str_with, default_translation_data, Options};
+
+#[test]
+fn changelog_lists_moved_inverted_added_and_dropped_fields() {
+    let input = r#"{
+        "Name": "Changes",
+        "MaxActiveEnemies": 60,
+        "HazardBonus": 1,
+        "EnemyDescriptors": {
+            "ED_Spider_Grunt": {"PawnStats": {"PST_FireResistance": 0.25}}
+        }
+    }"#;
+    let options = Options {
+        changelog: true,
+        ..Default::default()
+    };
+    let translation_data = default_translation_data().unwrap();
+    let (_, report) = convert_str_with(input, &translation_data, &options).unwrap();
+    let changes: Vec<String> = report.changelog.iter().map(ToString::to_string).collect();
+    for expected in [
+        "~ MaxActiveEnemies -> Caps.MaxActiveEnemies",
+        "+ DifficultySetting.BaseHazard = Hazard 5",
+        "~ EnemyDescriptors -> EnemiesNoSync",
+        "~ EnemyDescriptors.ED_Spider_Grunt.PawnStats.PST_FireResistance = 0.25 -> \
+         EnemiesNoSync.ED_Spider_Grunt.Resistances.FireDamageMultiplier = 0.75",
+        "- HazardBonus (deprecated)",
+    ] {
+        assert!(
+            changes.iter().any(|change| change == expected),
+            "{changes:#?}"
+        );
+    }
+}
+
+#[test]
+fn changelog_is_empty_unless_asked_for() {
+    let translation_data = default_translation_data().unwrap();
+    let (_, report) = convert_str_with(
+        r#"{"MaxActiveEnemies": 60}"#,
+        &translation_data,
+        &Options::default(),
+    )
+    .unwrap();
+    assert!(report.changelog.is_empty());
+}
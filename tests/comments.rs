@@ -0,0 +1,71 @@
+//! Comments of the source file written back with `keep_comments`.
+
+use cd2ifier::{convert_str_with, default_translation_data, Options};
+
+const COMMENTED: &str = r#"// Hazard 6 for teams of four
+{
+    "Name": "Commented",
+    // Shown in the difficulty menu
+    "Description": "Hard // but fair",
+    /* More nitra at the start
+       for the first resupply */
+    "StartingNitra": 80,
+    "ResupplyCost": 80,
+    // Fewer swarms
+    "MaxActiveSwarmers": 40,
+    "EnemyDescriptors": {
+        // Twice as many
+        "ED_Spider_Grunt": {
+            "MinSpawnCount": 2 // inline, dropped
+        }
+    }
+    // Last reviewed for season 5
+}
+"#;
+
+fn convert(options: &Options) -> String {
+    let translation_data = default_translation_data().unwrap();
+    convert_str_with(COMMENTED, &translation_data, options)
+        .unwrap()
+        .0
+}
+
+#[test]
+fn comments_are_dropped_by_default() {
+    let output = convert(&Options::default());
+    assert!(!output.contains("Fewer swarms"));
+    assert!(json::parse(&output).is_ok());
+}
+
+#[test]
+fn comments_follow_their_fields() {
+    let output = convert(&Options {
+        keep_comments: true,
+        ..Options::default()
+    });
+    let lines: Vec<&str> = output.lines().collect();
+    let before = |field: &str| {
+        let position = lines
+            .iter()
+            .position(|line| line.trim_start().starts_with(&format!("\"{field}\"")))
+            .unwrap();
+        lines[position - 1]
+    };
+    assert_eq!(lines[0], "// Hazard 6 for teams of four");
+    assert_eq!(before("Description"), "    // Shown in the difficulty menu");
+    assert_eq!(before("Resupply"), "    for the first resupply */");
+    assert_eq!(before("MaxActiveSwarmers"), "        // Fewer swarms");
+    assert_eq!(before("ED_Spider_Grunt"), "        // Twice as many");
+    assert_eq!(lines[lines.len() - 1], "// Last reviewed for season 5");
+    assert!(!output.contains("inline, dropped"));
+}
+
+#[test]
+fn comments_are_not_kept_in_compact_output() {
+    let output = convert(&Options {
+        keep_comments: true,
+        dont_pretty_print: true,
+        ..Options::default()
+    });
+    assert!(json::parse(&output).is_ok());
+}
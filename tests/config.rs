@@ -0,0 +1,100 @@
+//! Defaults for the flags read from a `cd2ifier.toml` in the working directory or above it, and
+//! from the `CD2IFIER_` environment variables.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A project directory holding a config file and a `levels` subdirectory with a CD1 file.
+fn project(name: &str, config: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-config-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("levels")).unwrap();
+    fs::write(dir.join("cd2ifier.toml"), config).unwrap();
+    fs::write(
+        dir.join("levels/level.json"),
+        r#"{"Name": "Config", "Description": "Config", "MaxActiveEnemies": 60}"#,
+    )
+    .unwrap();
+    dir
+}
+
+fn cd2ifier(dir: &Path, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+        .current_dir(dir.join("levels"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn config_sets_the_defaults_and_the_command_line_overrides_them() {
+    let dir = project(
+        "precedence",
+        "indent = 2\noutput-dir = \"converted\"\nquiet = true\n",
+    );
+
+    let output = cd2ifier(&dir, &["level.json"]);
+    assert!(output.status.success(), "{output:?}");
+    // Paths are relative to the config file:
+    let converted = fs::read_to_string(dir.join("converted/level.cd2.json")).unwrap();
+    assert!(converted.starts_with("{\n  \"Name\""), "{converted}");
+
+    // -o conflicts with --output-dir and --log-level with --quiet, so the config ones are left
+    // out:
+    let output = cd2ifier(
+        &dir,
+        &[
+            "level.json",
+            "-o",
+            "-",
+            "--indent",
+            "3",
+            "--log-level",
+            "warn",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("{\n   \"Name\""));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn unknown_config_flags_are_rejected() {
+    let dir = project("unknown", "indentation = 2\n");
+    let output = cd2ifier(&dir, &["level.json", "-o", "-"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("[indentation] is not a cd2ifier flag"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn environment_overrides_the_config_and_the_command_line_overrides_it() {
+    let dir = project("environment", "indent = 2\nquiet = true\n");
+    let cd2ifier = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .current_dir(dir.join("levels"))
+            .env("CD2IFIER_INDENT", "3")
+            .env("CD2IFIER_STRICT", "true")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = cd2ifier(&["level.json", "-o", "-", "--strict"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("{\n   \"Name\""));
+
+    let output = cd2ifier(&["level.json", "-o", "-", "--indent", "1"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("{\n \"Name\""));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,67 @@
+//! `--diff-against` compares the converted file with a previously converted one.
+
+use cd2ifier::Difference;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn differences_are_listed_by_path() {
+    let reference = r#"{"Name": "A", "Caps": {"MaxActiveEnemies": 60}, "Pools": {"Bins": [1, 2]}}"#;
+    let converted = r#"{"Caps": {"MaxActiveEnemies": 80}, "Pools": {"Bins": [1, 2, 3]}, "Name": "A",
+        "Description": "B"}"#;
+    let differences = cd2ifier::diff_str(reference, converted).unwrap();
+    assert_eq!(
+        differences,
+        vec![
+            Difference::Changed {
+                path: "Caps.MaxActiveEnemies".to_string(),
+                reference: 60.into(),
+                value: 80.into(),
+            },
+            Difference::Added {
+                path: "Pools.Bins.2".to_string(),
+                value: 3.into(),
+            },
+            Difference::Added {
+                path: "Description".to_string(),
+                value: "B".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn the_run_fails_when_the_conversion_differs() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-diff-against-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let source = dir.join("a.json");
+    fs::write(
+        &source,
+        r#"{"Name": "A", "Description": "A", "MaxActiveEnemies": 60}"#,
+    )
+    .unwrap();
+    let run = |reference: &str| {
+        let reference_file = dir.join("a.cd2.json");
+        fs::write(&reference_file, reference).unwrap();
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .arg(&source)
+            .arg("--validate-only")
+            .arg("--diff-against")
+            .arg(&reference_file)
+            .output()
+            .unwrap()
+    };
+
+    let same = run(&cd2ifier::convert_str(&fs::read_to_string(&source).unwrap()).unwrap());
+    assert!(same.status.success(), "{same:?}");
+
+    let differing = run(r#"{"Name": "A", "Description": "A", "Caps": {"MaxActiveEnemies": 50}}"#);
+    assert!(!differing.status.success());
+    let stderr = String::from_utf8(differing.stderr).unwrap();
+    assert!(
+        stderr.contains("~ Caps.MaxActiveEnemies = 50 -> 60"),
+        "{stderr}"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,67 @@
+//! Keys written twice in the source, of which the json crate silently keeps the last value.
+
+use std::fs;
+use std::process::Command;
+
+const CD1: &str = r#"{
+    "Name": "Duplicates",
+    "Description": "Duplicates",
+    "ResupplyCost": 60,
+    "EnemyDescriptors": {
+        "ED_Spider_Grunt": {"Base": "ED_Spider_Grunt", "Base": "ED_Spider_Grunt_Guard"}
+    },
+    // Bad merge:
+    "ResupplyCost": 80,
+}"#;
+
+#[test]
+fn duplicated_keys_are_reported_by_path() {
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (output, report) =
+        cd2ifier::convert_str_with(CD1, &translation_data, &Default::default()).unwrap();
+    assert_eq!(
+        report.duplicate_keys,
+        ["EnemyDescriptors.ED_Spider_Grunt.Base", "ResupplyCost"]
+    );
+    assert_eq!(report.warning_count(), 2);
+    // The last value is the one kept:
+    let cd2 = json::parse(&output).unwrap();
+    assert_eq!(cd2["Resupply"]["Cost"], 80);
+    assert_eq!(
+        report.to_json()["duplicate_keys"],
+        json::array!["EnemyDescriptors.ED_Spider_Grunt.Base", "ResupplyCost"]
+    );
+}
+
+#[test]
+fn duplicated_keys_fail_strict_mode_and_reach_the_report_file() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-duplicates-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.json"), CD1).unwrap();
+    let cd2ifier = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .current_dir(&dir)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = cd2ifier(&["level.json", "--strict", "-q"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Duplicated key [ResupplyCost]"));
+    assert!(!dir.join("level.cd2.json").exists());
+
+    let output = cd2ifier(&["level.json", "--report-file", "report.json", "-q"]);
+    assert!(output.status.success(), "{output:?}");
+    let report = json::parse(&fs::read_to_string(dir.join("report.json")).unwrap()).unwrap();
+    assert!(
+        report.dump().contains(
+            "\"duplicate_keys\":[\"EnemyDescriptors.ED_Spider_Grunt.Base\",\"ResupplyCost\"]"
+        ),
+        "{report}"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,29 @@
+//! Enemies that can be elite, with or without a vanilla elite enemy to be the elite version of.
+
+use json::object;
+
+fn elites_without_base(enemies: json::JsonValue) -> Vec<String> {
+    let cd1 = object! {"EnemyDescriptors": enemies};
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (_, report) = cd2ifier::convert(&cd1, &translation_data).unwrap();
+    report.elites_without_base
+}
+
+#[test]
+fn elites_with_a_vanilla_elite_base_are_not_warned_about() {
+    let enemies = object! {
+        "ED_Spider_Tank": {"Base": "ED_Spider_Tank", "Elite": true},
+        "ED_Custom_Guard": {"Base": "ED_Spider_Grunt_Guard", "Elite": 0.5},
+        "ED_Custom_Swarmer": {"Base": "ED_Spider_Swarmer", "Elite": false}
+    };
+    assert!(elites_without_base(enemies).is_empty());
+}
+
+#[test]
+fn elites_without_a_vanilla_elite_base_are_warned_about() {
+    let enemies = object! {
+        "ED_Custom_Swarmer": {"Base": "ED_Spider_Swarmer", "Elite": true},
+        "ED_Custom_Forced": {"Base": "ED_Spider_Swarmer", "Elite": true, "ForceEliteBase": "ED_Spider_Tank"}
+    };
+    assert_eq!(elites_without_base(enemies), ["ED_Custom_Swarmer"]);
+}
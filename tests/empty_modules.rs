@@ -0,0 +1,43 @@
+//! Modules that get no field are left out of the converted file.
+
+use cd2ifier::{convert_str_with, default_translation_data, Options};
+
+#[test]
+fn empty_modules_are_left_out() {
+    let input = r#"{
+        "Name": "Empty modules",
+        "Description": "Nothing goes into Pools or Caps",
+        "EnemyDescriptors": {},
+        "EscortMule": {},
+        "HazardBonus": 1
+    }"#;
+    let options = Options {
+        dont_pretty_print: true,
+        since_version: Some("9.9".to_string()),
+        ..Default::default()
+    };
+    let translation_data = default_translation_data().unwrap();
+    let (output, _) = convert_str_with(input, &translation_data, &options).unwrap();
+    assert!(!output.contains("{}"), "{output}");
+    assert!(!output.contains(r#""Pools""#), "{output}");
+    assert!(!output.contains(r#""EnemiesNoSync""#), "{output}");
+}
+
+#[test]
+fn modules_with_fields_are_kept() {
+    let options = Options {
+        dont_pretty_print: true,
+        ..Default::default()
+    };
+    let translation_data = default_translation_data().unwrap();
+    let (output, _) = convert_str_with(
+        r#"{"StationaryEnemies": ["ED_Spider_Grunt"]}"#,
+        &translation_data,
+        &options,
+    )
+    .unwrap();
+    assert!(
+        output.contains(r#""Pools":{"StationaryPool":["ED_Spider_Grunt"]}"#),
+        "{output}"
+    );
+}
@@ -0,0 +1,21 @@
+//! The converted enemies merged into an `EnemiesNoSync` that an earlier stage built, as the
+//! top modules of the translation data can.
+
+use json::object;
+
+#[test]
+fn converted_enemies_are_merged_into_an_earlier_enemies_no_sync() {
+    let mut translation_data = cd2ifier::default_translation_data().unwrap();
+    translation_data["TOP_MODULES"]["Grunt"] = "EnemiesNoSync".into();
+    translation_data["TOP_MODULES"]["Mactera"] = "EnemiesNoSync".into();
+    let cd1 = object! {
+        "Grunt": {"Base": "ED_Grunt", "Rarity": 2},
+        "Mactera": {"Base": "ED_Spider_Flyer"},
+        "EnemyDescriptors": {"Grunt": {"Base": "ED_Grunt", "Rarity": 5}}
+    };
+
+    let (cd2, report) = cd2ifier::convert(&cd1, &translation_data).unwrap();
+    assert_eq!(cd2["EnemiesNoSync"]["Mactera"]["Base"], "ED_Spider_Flyer");
+    assert_eq!(cd2["EnemiesNoSync"]["Grunt"]["Rarity"], 5);
+    assert_eq!(report.overwritten_enemies, ["Grunt"]);
+}
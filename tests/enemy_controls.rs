@@ -0,0 +1,61 @@
+//! Enemy controls written under an alias, with another casing or mistyped.
+
+use json::object;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn convert(controls: json::JsonValue) -> (json::JsonValue, cd2ifier::ConversionReport) {
+    let cd1 = object! {"EnemyDescriptors": {"ED_Spider_Grunt": controls}};
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (cd2, report) = cd2ifier::convert(&cd1, &translation_data).unwrap();
+    (cd2["EnemiesNoSync"]["ED_Spider_Grunt"].clone(), report)
+}
+
+#[test]
+fn aliases_and_miscased_controls_are_renamed() {
+    let (grunt, report) = convert(object! {"MinSpawn": 2, "maxspawncount": 4});
+    assert_eq!(grunt, object! {"MinSpawnCount": 2, "MaxSpawnCount": 4});
+    assert!(report.deprecated_enemy_controls.is_empty());
+}
+
+#[test]
+fn an_alias_doesnt_overwrite_the_control_given_under_its_canonical_name() {
+    for controls in [
+        object! {"MinSpawnCount": 2, "MinSpawn": 5},
+        object! {"MinSpawn": 5, "MinSpawnCount": 2},
+    ] {
+        let (grunt, report) = convert(controls);
+        assert_eq!(grunt, object! {"MinSpawnCount": 2});
+        assert_eq!(report.deprecated_enemy_controls.len(), 1);
+        assert_eq!(report.deprecated_enemy_controls[0].field, "MinSpawn");
+    }
+}
+
+#[test]
+fn mistyped_controls_are_dropped_with_a_suggestion() {
+    let (grunt, report) = convert(object! {"MaxSpwnCount": 4});
+    assert!(!grunt.has_key("MaxSpwnCount"));
+    assert_eq!(report.deprecated_enemy_controls[0].field, "MaxSpwnCount");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+        .args(["-", "--no-stamp"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(br#"{"EnemyDescriptors": {"ED_Spider_Grunt": {"MaxSpwnCount": 4, "Rarty": 1}}}"#)
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("[MaxSpwnCount] in [ED_Spider_Grunt]. Did you mean [MaxSpawnCount]?"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("Did you mean [Rarity]?"), "{stderr}");
+}
@@ -0,0 +1,85 @@
+//! Errors returned for files that can't be converted.
+
+use cd2ifier::ConversionError;
+
+#[test]
+fn parse_errors_point_to_the_line_of_the_source_file() {
+    // The multiline Description is cut down to one line before parsing, which must not shift
+    // the lines that follow it:
+    let input = "{\n\"Description\": \"First line\nSecond line\",\n\"Name\": }\n";
+    let error = cd2ifier::convert_str(input).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            ConversionError::Parse(json::Error::UnexpectedCharacter {
+                ch: '}',
+                line: 4,
+                column: 9
+            })
+        ),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.to_string(),
+        "The JSON parser found an unexpected character [}] at line 4, column 9. Is it a proper JSON?"
+    );
+}
+
+#[test]
+fn pawn_stats_going_to_an_unknown_module_are_refused_when_loading_the_translation_data() {
+    let mut translation_data = json::parse(cd2ifier::TRANSLATION_DATA).unwrap();
+    translation_data["PAWN_STATS"]["PST_CritChance"]["CD2_module"] = "none".into();
+    let error = cd2ifier::parse_translation_data(&translation_data.dump()).unwrap_err();
+    assert!(
+        matches!(&error, ConversionError::InvalidTranslationData(reason) if reason.contains("[none]")),
+        "{error:?}"
+    );
+
+    translation_data["PAWN_STATS"]["PST_CritChance"]["CD2_module"] = "Movement".into();
+    assert!(cd2ifier::parse_translation_data(&translation_data.dump()).is_ok());
+}
+
+#[test]
+fn translation_data_without_sanity_bounds_is_refused() {
+    let mut translation_data = json::parse(cd2ifier::TRANSLATION_DATA).unwrap();
+    translation_data.remove("SANITY_BOUNDS");
+    let error = cd2ifier::parse_translation_data(&translation_data.dump()).unwrap_err();
+    assert!(
+        matches!(&error, ConversionError::MissingTranslationData(keys) if keys == &["SANITY_BOUNDS"]),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn resistances_going_to_the_same_multiplier_are_refused_when_loading_the_translation_data() {
+    let mut translation_data = json::parse(cd2ifier::TRANSLATION_DATA).unwrap();
+    translation_data["PAWN_STATS"]["PST_ColdResistance"]["CD2_field"] =
+        "FireDamageMultiplier".into();
+    let error = cd2ifier::parse_translation_data(&translation_data.dump()).unwrap_err();
+    assert!(
+        matches!(&error, ConversionError::InvalidTranslationData(reason)
+            if reason.contains("[Resistances.FireDamageMultiplier]")),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn malformed_pawn_stats_are_refused_by_convert_without_panicking() {
+    let mut translation_data = cd2ifier::default_translation_data().unwrap();
+    translation_data["PAWN_STATS"]["PST_CritChance"] = json::object! {"CD2_module": "Movement"};
+    let cd1 = json::object! {
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_CritChance": 0.5}}}
+    };
+    let error = cd2ifier::convert(&cd1, &translation_data).unwrap_err();
+    assert!(
+        matches!(&error, ConversionError::InvalidTranslationData(reason)
+            if reason.contains("[PST_CritChance]")),
+        "{error:?}"
+    );
+
+    // The single stages can't fail, they skip the stat instead:
+    let mut report = Default::default();
+    let enemies = cd2ifier::convert_enemies(&cd1, &translation_data, &mut report);
+    assert!(!enemies["EnemiesNoSync"]["ED_Spider_Grunt"].has_key("Movement"));
+    assert_eq!(report.unsupported_pawn_stats.len(), 1);
+}
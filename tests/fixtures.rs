@@ -0,0 +1,53 @@
+//! Converts every fixture under `tests/fixtures` and compares the result with the expected file.
+//! Each fixture is a directory holding an `input.json` CD1 file and the `output.cd2.json` it
+//! must convert to, byte for byte.
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn fixtures_convert_to_the_expected_output() {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let mut fixtures: Vec<_> = fs::read_dir(&fixtures_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "No fixtures found");
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        let name = fixture.file_name().unwrap().to_string_lossy();
+        let input = fs::read_to_string(fixture.join("input.json")).unwrap();
+        let expected = fs::read_to_string(fixture.join("output.cd2.json")).unwrap();
+        match cd2ifier::convert_str_with(&input, &translation_data, &Default::default()) {
+            Ok((output, _)) if output == expected => (),
+            Ok((output, _)) => {
+                let line = output
+                    .lines()
+                    .zip(expected.lines())
+                    .position(|(output, expected)| output != expected)
+                    .map_or_else(
+                        || "the end".to_string(),
+                        |line| format!("line {}", line + 1),
+                    );
+                failures.push(format!("{name}: output differs at {line}:\n{output}"));
+            }
+            Err(e) => failures.push(format!("{name}: conversion failed: {e}")),
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
+
+#[test]
+fn reformatting_the_expected_outputs_leaves_them_unchanged() {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    for entry in fs::read_dir(&fixtures_dir).unwrap() {
+        let fixture = entry.unwrap().path();
+        let expected = fs::read_to_string(fixture.join("output.cd2.json")).unwrap();
+        let reformatted = cd2ifier::reformat_str(&expected, &Default::default()).unwrap();
+        assert_eq!(reformatted, expected, "{}", fixture.display());
+    }
+}
@@ -0,0 +1,53 @@
+//! Single entry arrays collapsed into plain values with `flatten`.
+
+use cd2ifier::{convert_with, default_translation_data, Options};
+use json::{array, object, JsonValue};
+
+fn convert(cd1: &JsonValue, flatten: bool) -> (JsonValue, cd2ifier::ConversionReport) {
+    let options = Options {
+        flatten,
+        ..Options::default()
+    };
+    convert_with(cd1, &default_translation_data().unwrap(), &options).unwrap()
+}
+
+#[test]
+fn single_entries_are_flattened() {
+    let cd1 = object! {
+        "StationaryEnemies": ["ED_Spider_Spawner"],
+        "MaxActiveEnemies": [60],
+        "StationaryDifficulty": [{"weight": 1, "range": {"min": 2, "max": 2}}],
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"Elite": [{"weight": 1, "range": {"min": 0.5, "max": 0.5}}]}}
+    };
+    let (cd2, report) = convert(&cd1, true);
+    assert_eq!(cd2["Pools"]["StationaryPool"], "ED_Spider_Spawner");
+    assert_eq!(cd2["Caps"]["MaxActiveEnemies"], 60);
+    assert_eq!(cd2["DifficultySetting"]["StationaryDifficulty"], 2);
+    assert_eq!(cd2["EnemiesNoSync"]["ED_Spider_Grunt"]["Elite"], 0.5);
+    assert!(report.unflattened_values.is_empty());
+}
+
+#[test]
+fn arrays_are_kept_without_flatten() {
+    let cd1 = object! {"StationaryEnemies": ["ED_Spider_Spawner"], "MaxActiveEnemies": [60]};
+    let (cd2, _) = convert(&cd1, false);
+    assert_eq!(cd2["Pools"]["StationaryPool"], array!["ED_Spider_Spawner"]);
+    assert_eq!(cd2["Caps"]["MaxActiveEnemies"], array![60]);
+}
+
+#[test]
+fn ambiguous_entries_are_kept_and_reported() {
+    let cd1 = object! {
+        "StationaryDifficulty": [{"weight": 1, "range": {"min": 1, "max": 2}}],
+        "MaxActiveEnemies": [40, 60]
+    };
+    let (cd2, report) = convert(&cd1, true);
+    assert_eq!(
+        cd2["DifficultySetting"]["StationaryDifficulty"],
+        array![{"weight": 1, "min": 1, "max": 2}]
+    );
+    assert_eq!(cd2["Caps"]["MaxActiveEnemies"], array![40, 60]);
+    assert_eq!(report.unflattened_values.len(), 1);
+    // Modules missing from the converted file are not added:
+    assert!(!cd2.has_key("Pools") && !cd2.has_key("EnemiesNoSync"));
+}
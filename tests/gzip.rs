@@ -0,0 +1,59 @@
+//! Compressed sources, read as they are, and `--gzip`, which compresses the converted file.
+
+// The files are compressed with flate2, a dependency of the command line tool only:
+#![cfg(feature = "cli")]
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::fs;
+use std::io::{Read, Write};
+use std::process::Command;
+
+const CD1: &str = r#"{"Name": "Gzip", "Description": "Gzip", "MaxActiveEnemies": 60}"#;
+
+fn gzip(text: &str) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes()).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn gunzip(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    GzDecoder::new(bytes).read_to_string(&mut text).unwrap();
+    text
+}
+
+#[test]
+fn compressed_sources_are_read_and_compressed_outputs_round_trip() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-gzip-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.json.gz"), gzip(CD1)).unwrap();
+    let cd2ifier = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .current_dir(&dir)
+            .args(args)
+            .args(["-q", "--no-stamp"])
+            .output()
+            .unwrap()
+    };
+    let expected = cd2ifier::convert_str(CD1).unwrap();
+
+    // The source is named after the file it holds:
+    let output = cd2ifier(&["level.json.gz"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        fs::read_to_string(dir.join("level.cd2.json")).unwrap(),
+        expected
+    );
+
+    let output = cd2ifier(&["level.json.gz", "--gzip", "--output-dir", "packed"]);
+    assert!(output.status.success(), "{output:?}");
+    let compressed = fs::read(dir.join("packed/level.cd2.json.gz")).unwrap();
+    assert_eq!(gunzip(&compressed), expected);
+
+    // The compressed output reads back as the same file:
+    let output = cd2ifier(&["packed/level.cd2.json.gz", "--reformat", "-o", "-"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+    fs::remove_dir_all(dir).unwrap();
+}
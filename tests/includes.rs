@@ -0,0 +1,117 @@
+//! `$include` directives merging other files into a CD1 file, with `resolve_includes_str` and
+//! `--resolve-includes`.
+
+use cd2ifier::ConversionError;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Resolves the includes of `input`, at `levels/level.json`, among the files of `files`.
+fn resolve(input: &str, files: &[(&str, &str)]) -> cd2ifier::Result<json::JsonValue> {
+    let files: HashMap<&Path, &str> = files
+        .iter()
+        .map(|(path, content)| (Path::new(*path), *content))
+        .collect();
+    let resolved = cd2ifier::resolve_includes_str(input, Path::new("levels/level.json"), |path| {
+        files
+            .get(path)
+            .map(|content| content.to_string())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    })?;
+    Ok(json::parse(&resolved).unwrap())
+}
+
+#[test]
+fn included_objects_are_merged_with_the_including_keys_first() {
+    let input = r#"{
+        "$include": ["common/hazard.json", "common/pools.json"],
+        "Name": "Level",
+        "MaxActiveEnemies": 40,
+        "EnemyDescriptors": {"$include": "enemies.json", "Grunt": {"Base": "ED_Grunt"}}
+    }"#;
+    let resolved = resolve(
+        input,
+        &[
+            (
+                "levels/common/hazard.json",
+                r#"{"BaseHazard": 4, "MaxActiveEnemies": 60}"#,
+            ),
+            (
+                "levels/common/pools.json",
+                r#"{"$include": "../enemies_pool.json", "BaseHazard": 5}"#,
+            ),
+            ("levels/enemies_pool.json", r#"{"EnemyPool": ["Grunt"]}"#),
+            (
+                "levels/enemies.json",
+                r#"{"Slasher": {"Base": "ED_Grunt_Slasher"}}"#,
+            ),
+        ],
+    )
+    .unwrap();
+    assert_eq!(resolved["BaseHazard"], 5);
+    assert_eq!(resolved["MaxActiveEnemies"], 40);
+    assert_eq!(resolved["EnemyPool"], json::array!["Grunt"]);
+    assert_eq!(resolved["Name"], "Level");
+    assert!(resolved["EnemyDescriptors"].has_key("Slasher"));
+    assert!(resolved["EnemyDescriptors"].has_key("Grunt"));
+    assert!(!resolved.has_key("$include"));
+}
+
+#[test]
+fn cycles_and_missing_files_are_refused() {
+    let error = resolve(
+        r#"{"$include": "a.json"}"#,
+        &[
+            ("levels/a.json", r#"{"$include": "b.json"}"#),
+            ("levels/b.json", r#"{"$include": "./a.json"}"#),
+        ],
+    )
+    .unwrap_err();
+    assert!(
+        matches!(&error, ConversionError::InvalidSource(reason) if reason.contains("cycle")),
+        "{error:?}"
+    );
+
+    let error = resolve(r#"{"$include": "missing.json"}"#, &[]).unwrap_err();
+    assert!(error.to_string().contains("couldn't be read"), "{error}");
+}
+
+#[test]
+fn includes_are_resolved_only_when_asked_for() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-includes-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let level = dir.join("level.json");
+    fs::write(
+        &level,
+        "{\"$include\": \"caps.json\", \"Name\": \"Level\",\n\"Description\": \"First line\nSecond line\"}",
+    )
+    .unwrap();
+    fs::write(dir.join("caps.json"), r#"{"MaxActiveEnemies": 33}"#).unwrap();
+
+    let convert = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .arg(&level)
+            .args(["-o", "-", "-q"])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = convert(&["--resolve-includes"]);
+    assert!(output.status.success(), "{output:?}");
+    let converted = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        converted.contains("\"MaxActiveEnemies\": 33"),
+        "{converted}"
+    );
+    assert!(converted.contains("First line\nSecond line"), "{converted}");
+
+    let output = convert(&[]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(!String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("\"MaxActiveEnemies\": 33"));
+    fs::remove_dir_all(dir).unwrap();
+}
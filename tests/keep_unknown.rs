@@ -0,0 +1,84 @@
+//! `Options::keep_unknown`, keeping what can't be converted under an `x-unconverted` object
+//! instead of dropping it.
+
+use cd2ifier::Options;
+use json::object;
+
+const CD1: &str = r#"{
+    "Name": "Unknown",
+    "Description": "Unknown",
+    "MaxActiveEnemies": 60,
+    "NotAField": [1, 2],
+    "EnemyDescriptors": {
+        "ED_Spider_Grunt": {
+            "Rarity": 1,
+            "NotAControl": true,
+            "PawnStats": {"PST_NotAStat": 0.5}
+        }
+    }
+}"#;
+
+fn convert(keep_unknown: bool) -> (json::JsonValue, cd2ifier::ConversionReport) {
+    let options = Options {
+        keep_unknown,
+        check_schema: true,
+        ..Default::default()
+    };
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (output, report) = cd2ifier::convert_str_with(CD1, &translation_data, &options).unwrap();
+    (json::parse(&output).unwrap(), report)
+}
+
+#[test]
+fn unknown_fields_are_kept_in_the_structure_of_the_source() {
+    let (cd2, report) = convert(true);
+    assert_eq!(
+        cd2["x-unconverted"],
+        object! {
+            "NotAField": [1, 2],
+            "EnemyDescriptors": {
+                "ED_Spider_Grunt": {"NotAControl": true, "PawnStats": {"PST_NotAStat": 0.5}}
+            }
+        }
+    );
+    // It is the last key, and the schema check, which sees it, lets it be:
+    assert_eq!(cd2.entries().next_back().unwrap().0, "x-unconverted");
+    assert!(report.schema_violations.is_empty(), "{report}");
+    assert_eq!(
+        cd2["EnemiesNoSync"]["ED_Spider_Grunt"],
+        object! {"Rarity": 1}
+    );
+}
+
+#[test]
+fn unknown_fields_are_dropped_by_default() {
+    let (cd2, report) = convert(false);
+    assert!(!cd2.has_key("x-unconverted"));
+    assert!(!cd2.has_key("NotAField"));
+    assert_eq!(report.unsupported_fields, ["NotAField"]);
+}
+
+#[test]
+fn multiline_unknown_fields_are_kept_whole() {
+    let input = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/multiline_unknown_field/input.json"
+    ))
+    .unwrap();
+    let options = Options {
+        keep_unknown: true,
+        ..Default::default()
+    };
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (output, _) = cd2ifier::convert_str_with(&input, &translation_data, &options).unwrap();
+    // The unknown field is nested, so its lines are joined with escaped line endings:
+    assert!(
+        output.contains(r#""Notes": "line one\n  line two\nline three""#),
+        "{output}"
+    );
+    // The multiline Description is still recovered over several lines:
+    assert!(
+        output.contains("\"Description\": \"Kept\nover two lines\","),
+        "{output}"
+    );
+}
@@ -0,0 +1,50 @@
+//! The `max_depth` and `max_bytes` limits, which refuse untrusted input before parsing it.
+
+use cd2ifier::{ConversionError, Options};
+
+/// Three levels deep: the file, the Caps module and the per-player array.
+const CD1: &str =
+    r#"{"Name": "Limits", "Description": "Limits", "Caps": {"MaxActiveEnemies": [60]}}"#;
+
+fn convert(options: Options) -> cd2ifier::Result<(String, cd2ifier::ConversionReport)> {
+    cd2ifier::convert_str_with(CD1, &cd2ifier::default_translation_data()?, &options)
+}
+
+#[test]
+fn files_nested_deeper_than_the_limit_are_refused() {
+    let at_limit = Options {
+        max_depth: Some(3),
+        ..Default::default()
+    };
+    assert!(convert(at_limit).is_ok());
+
+    let over_limit = Options {
+        max_depth: Some(2),
+        ..Default::default()
+    };
+    let error = convert(over_limit).unwrap_err();
+    assert!(
+        matches!(&error, ConversionError::InvalidSource(reason) if reason.contains("limit of 2 levels")),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn files_larger_than_the_limit_are_refused() {
+    let at_limit = Options {
+        max_bytes: Some(CD1.len()),
+        ..Default::default()
+    };
+    assert!(convert(at_limit).is_ok());
+
+    let over_limit = Options {
+        max_bytes: Some(CD1.len() - 1),
+        ..Default::default()
+    };
+    let error = convert(over_limit).unwrap_err();
+    assert!(
+        matches!(&error, ConversionError::InvalidSource(reason)
+            if reason.contains(&format!("limit of {} bytes", CD1.len() - 1))),
+        "{error:?}"
+    );
+}
@@ -0,0 +1,38 @@
+//! `--list-unsupported` counts what couldn't be translated across all the source files.
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn untranslated_parts_are_counted_across_files() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-unsupported-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("a.json"),
+        r#"{"Name": "A", "Description": "A", "WeirdField": 1,
+            "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_Foo": 1}}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("b.json"),
+        r#"{"Name": "B", "Description": "B", "WeirdField": 2, "OtherField": 3}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+        .arg(&dir)
+        .args(["--list-unsupported", "-q"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let listed = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        listed,
+        "Unsupported fields:\n     2  WeirdField\n     1  OtherField\n\
+         Unsupported pawn stats:\n     1  PST_Foo\n"
+    );
+    // Nothing is written:
+    assert!(!dir.join("a.cd2.json").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
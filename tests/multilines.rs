@@ -0,0 +1,57 @@
+//! Descriptions holding escaped quotes, which must not be taken for the end of the string, and
+//! multiline strings nested below the top level or following comments with quotes or braces.
+
+use cd2ifier::{convert_str_with, default_translation_data, Options};
+
+fn compact_description(input: &str) -> json::JsonValue {
+    let options = Options {
+        dont_pretty_print: true,
+        ..Default::default()
+    };
+    let translation_data = default_translation_data().unwrap();
+    let (output, _) = convert_str_with(input, &translation_data, &options).unwrap();
+    json::parse(&output).unwrap()["Description"].clone()
+}
+
+#[test]
+fn single_line_description_with_escaped_quotes_is_not_a_multiline() {
+    let input = "{\n    \"Description\": \"Say \\\"hi\\\", then \\\"bye\\\"\",\n    \"MaxActiveEnemies\": 60\n}";
+    assert_eq!(compact_description(input), "Say \"hi\", then \"bye\"");
+}
+
+#[test]
+fn multiline_description_whose_first_line_ends_with_an_escaped_quote() {
+    let input = "{\n    \"Description\": \"Ends with \\\",\nand goes on\",\n    \"MaxActiveEnemies\": 60\n}";
+    assert_eq!(compact_description(input), "Ends with \",\nand goes on");
+}
+
+#[test]
+fn nested_multiline_strings_are_joined_into_a_single_line() {
+    let input = "{\n    \"Name\": \"Nested\",\n    \"Caps\": {\n        \"Description\": \"First line\nSecond line\",\n        \"MaxActiveEnemies\": 60\n    }\n}";
+    let output = cd2ifier::reformat_str(input, &Options::default()).unwrap();
+    let cd2 = json::parse(&output).unwrap();
+    assert_eq!(cd2["Caps"]["Description"], "First line\nSecond line");
+    assert_eq!(cd2["Caps"]["MaxActiveEnemies"], 60);
+}
+
+#[test]
+fn a_quote_in_a_comment_does_not_hide_a_multiline_description() {
+    let input = "{\n    // A 12\" hazard\n    \"Description\": \"First line\nSecond line\",\n    \"MaxActiveEnemies\": 60\n}";
+    assert_eq!(compact_description(input), "First line\nSecond line");
+}
+
+#[test]
+fn a_brace_in_a_comment_keeps_the_description_at_the_top_level() {
+    let input = "{\n    /* Was { \"Hard\": true } */\n    \"Description\": \"First line\nSecond line\",\n    \"MaxActiveEnemies\": 60\n}";
+    let output = cd2ifier::convert_str(input).unwrap();
+    assert!(
+        output.contains("\"Description\": \"First line\nSecond line\""),
+        "{output}"
+    );
+    let input = "{\n    // {\n    \"Description\": \"First line\nSecond line\",\n    \"MaxActiveEnemies\": 60\n}";
+    let output = cd2ifier::convert_str(input).unwrap();
+    assert!(
+        output.contains("\"Description\": \"First line\nSecond line\""),
+        "{output}"
+    );
+}
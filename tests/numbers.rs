@@ -0,0 +1,40 @@
+//! Numbers that end up NaN or infinite in the converted file, which the game can't read.
+
+use cd2ifier::{convert_str_with, default_translation_data, Options};
+
+#[test]
+fn infinite_numbers_are_reported_with_their_path() {
+    let input = r#"{
+        "Name": "Numbers",
+        "Description": "Numbers",
+        "MaxActiveEnemies": 1e400,
+        "EnemyCountModifier": [1, 2, -1e400, 4],
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_DamageResistance": 1e400}}}
+    }"#;
+    let translation_data = default_translation_data().unwrap();
+    let (_, report) = convert_str_with(input, &translation_data, &Options::default()).unwrap();
+    assert_eq!(
+        report.non_finite_numbers,
+        [
+            "DifficultySetting.EnemyCountModifier.2",
+            "EnemiesNoSync.ED_Spider_Grunt.Resistances.DamageMultiplier",
+            "Caps.MaxActiveEnemies",
+        ]
+    );
+    assert_eq!(
+        report.strict_issues().len(),
+        3,
+        "{:?}",
+        report.strict_issues()
+    );
+}
+
+#[test]
+fn an_infinite_starting_nitra_is_not_used() {
+    let input = r#"{"StartingNitra": 1e400, "ResupplyCost": 80}"#;
+    let translation_data = default_translation_data().unwrap();
+    let (output, report) = convert_str_with(input, &translation_data, &Options::default()).unwrap();
+    assert!(output.contains(r#""Cost": 80"#), "{output}");
+    assert_eq!(report.resupply_warnings.len(), 1);
+    assert!(report.non_finite_numbers.is_empty());
+}
@@ -0,0 +1,72 @@
+//! Conversion options that change the layout of the output.
+
+use cd2ifier::{convert_str_with, default_translation_data, Options};
+
+const UNSORTED: &str = r#"{
+    "Name": "Sorting",
+    "Description": "Sorting",
+    "EnemyDescriptors": {
+        "ED_Spider_Grunt": {"MinSpawnCount": 2, "MaxSpawnCount": 4},
+        "ED_Spider_Exploder": {"Rarity": 1, "MaxSpawnCount": 3}
+    }
+}"#;
+
+fn convert(options: &Options) -> String {
+    let translation_data = default_translation_data().unwrap();
+    let options = Options {
+        dont_pretty_print: true,
+        ..options.clone()
+    };
+    convert_str_with(UNSORTED, &translation_data, &options)
+        .unwrap()
+        .0
+}
+
+#[test]
+fn enemies_keep_the_source_order_by_default() {
+    assert!(convert(&Options::default()).contains(
+        r#""EnemiesNoSync":{"ED_Spider_Grunt":{"MinSpawnCount":2,"MaxSpawnCount":4},"ED_Spider_Exploder":{"Rarity":1,"MaxSpawnCount":3}}"#
+    ));
+}
+
+#[test]
+fn sort_enemies_keeps_the_order_of_the_controls() {
+    let options = Options {
+        sort_enemies: true,
+        ..Default::default()
+    };
+    assert!(convert(&options).contains(
+        r#""EnemiesNoSync":{"ED_Spider_Exploder":{"Rarity":1,"MaxSpawnCount":3},"ED_Spider_Grunt":{"MinSpawnCount":2,"MaxSpawnCount":4}}"#
+    ));
+}
+
+#[test]
+fn sort_keys_also_sorts_the_controls() {
+    let options = Options {
+        sort_enemies: true,
+        sort_keys: true,
+        ..Default::default()
+    };
+    assert!(convert(&options).contains(
+        r#""EnemiesNoSync":{"ED_Spider_Exploder":{"MaxSpawnCount":3,"Rarity":1},"ED_Spider_Grunt":{"MaxSpawnCount":4,"MinSpawnCount":2}}"#
+    ));
+}
+
+#[test]
+fn output_ends_with_the_line_ending_of_the_source() {
+    let translation_data = default_translation_data().unwrap();
+    let input = "{\r\n\"Name\": \"Newline\",\r\n\"Description\": \"First\r\nSecond\"\r\n}\r\n";
+    let (output, _) = convert_str_with(input, &translation_data, &Options::default()).unwrap();
+    assert!(output.ends_with("}\r\n") && !output.ends_with("\r\n\r\n"));
+    let reformatted = cd2ifier::reformat_str(&output, &Options::default()).unwrap();
+    assert_eq!(reformatted, output);
+}
+
+#[test]
+fn no_final_newline() {
+    let options = Options {
+        final_newline: false,
+        ..Default::default()
+    };
+    assert!(convert(&options).ends_with('}'));
+}
@@ -0,0 +1,140 @@
+//! Target file names derived with `--output-suffix` and `--output-extension`, and the
+//! directories created for the target files. Sources without a file name to derive
+//! the target from, like `missing/..`, are refused.
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn derived_names_use_the_given_suffix_and_extension() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-names-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("level.json"),
+        r#"{"Name": "Names", "Description": "Names"}"#,
+    )
+    .unwrap();
+    let cd2ifier = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .current_dir(&dir)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    assert!(cd2ifier(&[
+        "level.json",
+        "--output-suffix",
+        "_cd2",
+        "--output-extension",
+        "txt"
+    ])
+    .status
+    .success());
+    assert!(dir.join("level_cd2.txt").is_file());
+
+    assert!(
+        cd2ifier(&["level.json", "--output-suffix", "", "--output-dir", "flat"])
+            .status
+            .success()
+    );
+    assert!(dir.join("flat/level.json").is_file());
+
+    // Without an output directory, the source would be overwritten:
+    let output = cd2ifier(&["level.json", "--output-suffix", "", "--force"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        fs::read_to_string(dir.join("level.json")).unwrap(),
+        r#"{"Name": "Names", "Description": "Names"}"#
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn missing_directories_of_the_targets_are_created() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-target-dirs-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("levels/hard")).unwrap();
+    let cd1 = r#"{"Name": "Dirs", "Description": "Dirs"}"#;
+    fs::write(dir.join("levels/hard/level.json"), cd1).unwrap();
+    let cd2ifier = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .current_dir(&dir)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = cd2ifier(&["levels", "-r", "--output-dir", "out/new/tree", "-q"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(dir.join("out/new/tree/hard/level.cd2.json").is_file());
+
+    let output = cd2ifier(&[
+        "levels/hard/level.json",
+        "-o",
+        "single/nested/level.json",
+        "-q",
+    ]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(dir.join("single/nested/level.json").is_file());
+
+    // A file in the way of a directory can't be replaced:
+    fs::write(dir.join("blocked"), "").unwrap();
+    let output = cd2ifier(&["levels/hard/level.json", "-o", "blocked/level.json", "-q"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Could not create the directory blocked"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn unusual_source_paths_are_named_or_refused_without_panicking() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-odd-names-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for sub in ["sub", "dot", "up/inner"] {
+        fs::create_dir_all(dir.join(sub)).unwrap();
+    }
+    let cd1 = r#"{"Name": "Odd", "Description": "Odd"}"#;
+    for level in [
+        "foo.bar.json",
+        "sub/level.json",
+        "dot/level.json",
+        "up/level.json",
+    ] {
+        fs::write(dir.join(level), cd1).unwrap();
+    }
+    let cd2ifier = |current_dir: &str, source: &str| {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .current_dir(dir.join(current_dir))
+            .args([source, "-q"])
+            .output()
+            .unwrap()
+    };
+
+    // Only the last extension is replaced:
+    let output = cd2ifier("", "foo.bar.json");
+    assert!(output.status.success(), "{output:?}");
+    assert!(dir.join("foo.bar.cd2.json").is_file());
+
+    // A path ending in a separator, `.` and `..` are directories, whose files are converted:
+    let output = cd2ifier("", "sub/");
+    assert!(output.status.success(), "{output:?}");
+    assert!(dir.join("sub/level.cd2.json").is_file());
+    let output = cd2ifier("dot", ".");
+    assert!(output.status.success(), "{output:?}");
+    assert!(dir.join("dot/level.cd2.json").is_file());
+    let output = cd2ifier("up/inner", "..");
+    assert!(output.status.success(), "{output:?}");
+    assert!(dir.join("up/level.cd2.json").is_file());
+
+    // Without a file name to derive the target from, the conversion fails cleanly:
+    let output = cd2ifier("", "missing/..");
+    assert!(!output.status.success());
+    assert_ne!(output.status.code(), Some(101), "{output:?}");
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Could not derive an output file name from missing/.."));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,62 @@
+//! Values forced at JSON pointers of the converted file with `Options::overrides` and `--set`.
+
+use cd2ifier::{ConversionError, Options, Override};
+use std::process::Command;
+
+const CD1: &str = r#"{"Name": "Overrides", "BaseHazard": 5, "EnemyPool": ["Grunt", "Slasher"]}"#;
+
+fn convert(overrides: &[&str]) -> cd2ifier::Result<json::JsonValue> {
+    let options = Options {
+        overrides: overrides.iter().map(|over| over.parse().unwrap()).collect(),
+        ..Options::default()
+    };
+    let translation_data = cd2ifier::default_translation_data()?;
+    let (output, _) = cd2ifier::convert_str_with(CD1, &translation_data, &options)?;
+    Ok(json::parse(&output).unwrap())
+}
+
+#[test]
+fn overrides_replace_add_and_append_values() {
+    let converted = convert(&[
+        "/DifficultySetting/BaseHazard=4",
+        "/Pools/EnemyPool/0=\"Mactera\"",
+        "/Pools/EnemyPool/-=\"Praetorian\"",
+        "/EscortMule/Speed~1Mode=[1.5]",
+    ])
+    .unwrap();
+    assert_eq!(converted["DifficultySetting"]["BaseHazard"], 4);
+    assert_eq!(
+        converted["Pools"]["EnemyPool"],
+        json::array!["Mactera", "Slasher", "Praetorian"]
+    );
+    assert_eq!(converted["EscortMule"]["Speed/Mode"], json::array![1.5]);
+}
+
+#[test]
+fn overrides_going_through_a_value_fail() {
+    let error = convert(&["/Name/First=1"]).unwrap_err();
+    assert!(matches!(error, ConversionError::InvalidOverride(_)));
+    let error = convert(&["/Pools/EnemyPool/5=1"]).unwrap_err();
+    assert!(matches!(error, ConversionError::InvalidOverride(_)));
+}
+
+#[test]
+fn malformed_overrides_are_refused() {
+    for over in [
+        "DifficultySetting/BaseHazard=4",
+        "/DifficultySetting/BaseHazard",
+        "/DifficultySetting/BaseHazard=Hazard 4",
+        "/Difficulty~2Setting=4",
+    ] {
+        assert!(over.parse::<Override>().is_err(), "{over}");
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+        .args(["-", "--set", "/DifficultySetting/BaseHazard=Hazard 4"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("is not valid JSON"));
+}
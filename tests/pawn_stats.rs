@@ -0,0 +1,82 @@
+//! Pawn stats whose CD2 target depends on the CD2 version, given as a list of targets in the
+//! translation data.
+
+use cd2ifier::{convert_with, default_translation_data, Options};
+use json::{array, object, JsonValue};
+
+fn convert_for(target_version: Option<&str>) -> (JsonValue, cd2ifier::ConversionReport) {
+    let mut translation_data = default_translation_data().unwrap();
+    translation_data["PAWN_STATS"]["PST_MovementSpeed"] = array![
+        {"version": "1.0", "CD2_module": "None", "CD2_field": "PST_MovementSpeed"},
+        {"version": "1.2", "CD2_module": "Movement", "CD2_field": "SpeedMultiplier"}
+    ];
+    translation_data["PAWN_STATS"]["PST_Stagger"] =
+        array![{"version": "1.3", "CD2_module": "Movement", "CD2_field": "Stagger"}];
+    let cd1 = object! {
+        "EnemyDescriptors": {"ED_Spider_Grunt": {
+            "PawnStats": {"PST_MovementSpeed": 1.5, "PST_Stagger": 2}
+        }}
+    };
+    let options = Options {
+        target_version: target_version.map(str::to_string),
+        ..Default::default()
+    };
+    let (cd2, report) = convert_with(&cd1, &translation_data, &options).unwrap();
+    (cd2["EnemiesNoSync"]["ED_Spider_Grunt"].clone(), report)
+}
+
+#[test]
+fn the_latest_target_is_used_by_default() {
+    let (grunt, report) = convert_for(None);
+    assert_eq!(grunt["Movement"]["SpeedMultiplier"], 1.5);
+    assert_eq!(grunt["Movement"]["Stagger"], 2);
+    assert!(report.unsupported_pawn_stats.is_empty());
+}
+
+#[test]
+fn the_target_of_the_requested_version_is_used() {
+    let (grunt, _) = convert_for(Some("1.1"));
+    assert_eq!(grunt["PST_MovementSpeed"], 1.5);
+    assert!(!grunt.has_key("Movement"));
+    let (grunt, _) = convert_for(Some("1.2"));
+    assert_eq!(grunt["Movement"]["SpeedMultiplier"], 1.5);
+}
+
+#[test]
+fn stats_from_later_versions_are_unsupported() {
+    let (grunt, report) = convert_for(Some("1.2"));
+    assert!(!grunt["Movement"].has_key("Stagger"));
+    assert_eq!(report.unsupported_pawn_stats.len(), 1);
+    assert_eq!(report.unsupported_pawn_stats[0].field, "PST_Stagger");
+}
+
+#[test]
+fn pawn_stats_going_to_the_same_control_are_reported() {
+    let mut translation_data = default_translation_data().unwrap();
+    translation_data["PAWN_STATS"]["PST_Health"] =
+        object! {"CD2_module": "None", "CD2_field": "Health"};
+    translation_data["PAWN_STATS"]["PST_MaxHealth"] =
+        object! {"CD2_module": "None", "CD2_field": "Health"};
+    let cd1 = object! {
+        "EnemyDescriptors": {
+            "ED_Spider_Grunt": {"PawnStats": {"PST_Health": 100, "PST_MaxHealth": 200}},
+            "ED_Spider_Exploder": {"PawnStats": {"PST_Health": 50}}
+        }
+    };
+    let (cd2, report) = convert_with(&cd1, &translation_data, &Options::default()).unwrap();
+    assert_eq!(cd2["EnemiesNoSync"]["ED_Spider_Grunt"]["Health"], 200);
+    assert_eq!(report.overwritten_pawn_stats.len(), 1);
+    let overwritten = &report.overwritten_pawn_stats[0];
+    assert_eq!(
+        (
+            overwritten.enemy.as_str(),
+            overwritten.stat.as_str(),
+            overwritten.target.as_str()
+        ),
+        ("ED_Spider_Grunt", "PST_Health", "Health")
+    );
+    assert_eq!(
+        report.warning_count(),
+        report.missing_recommended_fields.len() + 1
+    );
+}
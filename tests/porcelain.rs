@@ -0,0 +1,45 @@
+//! The single line JSON summaries printed with `--porcelain`, which scripts parse.
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn porcelain_lines_are_exact() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-porcelain-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("good.json"),
+        r#"{"Name": "Porcelain", "Description": "Porcelain", "MaxActiveEnemies": 60}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("bad.json"), r#"{"Name": "#).unwrap();
+    let porcelain = |source: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .current_dir(&dir)
+            .args([source, "--porcelain", "-q"])
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(
+        porcelain("good.json"),
+        r#"{"source":"good.json","target":"good.cd2.json","status":"ok","warnings":0}"#.to_owned()
+            + "\n"
+    );
+    assert_eq!(
+        porcelain("bad.json"),
+        r#"{"source":"bad.json","target":"bad.cd2.json","status":"failed","warnings":null,"error":"The file ended before the JSON was complete. Is a closing bracket or quote missing?"}"#
+            .to_owned()
+            + "\n"
+    );
+    // A file that converts but can't be written still has its warnings counted:
+    assert_eq!(
+        porcelain("good.json"),
+        r#"{"source":"good.json","target":"good.cd2.json","status":"failed","warnings":0,"error":"Target file good.cd2.json already exists, pass --force to overwrite it"}"#
+            .to_owned()
+            + "\n"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
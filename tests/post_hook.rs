@@ -0,0 +1,35 @@
+//! `--post-hook` runs a command on each written file.
+
+#![cfg(unix)]
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn the_hook_gets_the_path_of_each_converted_file() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-hook-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("levels")).unwrap();
+    for name in ["a", "b"] {
+        let cd1 = format!(r#"{{"Name": "{name}", "Description": "Hook"}}"#);
+        fs::write(dir.join(format!("levels/{name}.json")), cd1).unwrap();
+    }
+    let cd2ifier = |hook: &str| {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .current_dir(&dir)
+            .args(["levels", "--force", "--post-hook", hook])
+            .output()
+            .unwrap()
+    };
+
+    let output = cd2ifier("echo >> hooked.txt");
+    assert!(output.status.success(), "{output:?}");
+    let hooked = fs::read_to_string(dir.join("hooked.txt")).unwrap();
+    let mut hooked: Vec<&str> = hooked.lines().collect();
+    hooked.sort();
+    assert_eq!(hooked, ["levels/a.cd2.json", "levels/b.cd2.json"]);
+
+    // A failing hook fails the files it ran for:
+    assert!(!cd2ifier("false").status.success());
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,20 @@
+//! The one line summary of a `ConversionReport`, logged for each converted file.
+
+fn report(cd1: &str) -> cd2ifier::ConversionReport {
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    cd2ifier::convert_str_with(cd1, &translation_data, &Default::default())
+        .unwrap()
+        .1
+}
+
+#[test]
+fn only_the_issues_found_are_listed() {
+    let clean = report(r#"{"Name": "Clean", "Description": "Clean", "MaxActiveEnemies": 60}"#);
+    assert_eq!(clean.to_string(), "no issues");
+
+    let issues = report(r#"{"Name": "Issues", "MaxActiveEnemies": 60, "A": 1, "B": 2}"#);
+    assert_eq!(
+        issues.to_string(),
+        "2 unsupported fields, 1 missing recommended fields"
+    );
+}
@@ -0,0 +1,91 @@
+//! `--report-file`, the JSON report of a run: one entry for a single file, an array of them for
+//! a batch, written even when some file failed.
+
+use json::JsonValue;
+use std::fs;
+use std::process::Command;
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+        .current_dir(dir)
+        .args(args)
+        .arg("-q")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn a_single_file_gets_a_single_entry() {
+    let dir = temp_dir("report-single");
+    fs::write(
+        dir.join("level.json"),
+        r#"{"Name": "Report", "MaxActiveEnemies": 60, "NotAField": 1,
+            "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_FireResistance": 0.25}}}}"#,
+    )
+    .unwrap();
+
+    let output = run(&dir, &["level.json", "--report-file", "report.json"]);
+    assert!(output.status.success(), "{output:?}");
+    let entry = json::parse(&fs::read_to_string(dir.join("report.json")).unwrap()).unwrap();
+    assert!(entry.is_object(), "{entry}");
+    assert_eq!(entry["source"], "level.json");
+    assert_eq!(entry["status"], "ok");
+    assert!(entry["timestamp"].as_u64().is_some_and(|time| time > 0));
+    assert!(entry["milliseconds"].is_number());
+    // The missing Description and the unsupported field:
+    assert_eq!(entry["warnings"], 2);
+    let report = &entry["report"];
+    assert_eq!(report["unsupported_fields"], json::array!["NotAField"]);
+    assert_eq!(
+        report["missing_recommended_fields"],
+        json::array!["Description"]
+    );
+    assert_eq!(report["moved_fields"], json::array!["MaxActiveEnemies"]);
+    assert_eq!(report["converted_enemies"], json::array!["ED_Spider_Grunt"]);
+    assert_eq!(
+        report["remapped_pawn_stats"],
+        json::array![{
+            "enemy": "ED_Spider_Grunt",
+            "stat": "PST_FireResistance",
+            "target": "Resistances.FireDamageMultiplier"
+        }]
+    );
+    for key in [
+        "deprecated_fields",
+        "deprecated_enemy_controls",
+        "elite_base_overrides",
+    ] {
+        assert!(report[key].is_array(), "{key}");
+    }
+    assert_eq!(report["already_cd2"], false);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn a_batch_gets_an_entry_per_file_even_if_one_fails() {
+    let dir = temp_dir("report-batch");
+    fs::write(dir.join("a.json"), r#"{"Name": "A", "Description": "A"}"#).unwrap();
+    fs::write(dir.join("b.json"), "{ not json").unwrap();
+
+    let output = run(&dir, &["a.json", "b.json", "--report-file", "report.json"]);
+    assert!(!output.status.success());
+    let entries = json::parse(&fs::read_to_string(dir.join("report.json")).unwrap()).unwrap();
+    let mut entries: Vec<&JsonValue> = entries.members().collect();
+    entries.sort_by_key(|entry| entry["source"].as_str().unwrap().to_string());
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["source"], "a.json");
+    assert_eq!(entries[0]["status"], "ok");
+    assert_eq!(entries[0]["warnings"], 0);
+    assert_eq!(entries[1]["source"], "b.json");
+    assert_eq!(entries[1]["status"], "failed");
+    assert!(entries[1]["error"].as_str().unwrap().contains("JSON"));
+    assert!(!entries[1].has_key("report"));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,104 @@
+//! Resistance pawn stats in files that may have been converted to CD2 already, and outside of
+//! the 0 to 1 range.
+
+use json::object;
+
+fn possibly_inverted(cd1: &json::JsonValue) -> usize {
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (_, report) = cd2ifier::convert(cd1, &translation_data).unwrap();
+    report.possibly_inverted_resistances.len()
+}
+
+#[test]
+fn resistances_of_a_cd1_file_are_not_suspicious() {
+    let cd1 = object! {
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_FireResistance": 0.8}}}
+    };
+    assert_eq!(possibly_inverted(&cd1), 0);
+}
+
+#[test]
+fn resistances_of_a_cd1_file_with_a_revision_number_are_not_suspicious() {
+    let cd1 = object! {
+        "Version": "2.3",
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_FireResistance": 0.8}}}
+    };
+    assert_eq!(possibly_inverted(&cd1), 0);
+}
+
+#[test]
+fn resistances_next_to_a_stationary_pool_are_warned_about() {
+    let cd1 = object! {
+        "StationaryPool": ["ED_Spider_Stationary_Lobber"],
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_FireResistance": 0.8}}}
+    };
+    assert_eq!(possibly_inverted(&cd1), 1);
+}
+
+#[test]
+fn resistances_next_to_cd2_modules_are_warned_about() {
+    let cd1 = object! {
+        "Caps": {"MaxActiveEnemies": 60},
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_FireResistance": 0.8}}}
+    };
+    assert_eq!(possibly_inverted(&cd1), 1);
+}
+
+#[test]
+fn resistances_next_to_cd2_enemy_modules_are_warned_about() {
+    let cd1 = object! {
+        "EnemyDescriptors": {"ED_Spider_Grunt": {
+            "PawnStats": {"PST_FireResistance": 0.8, "PST_DamageResistance": 0.5},
+            "Resistances": {"ColdDamageMultiplier": 0.5}
+        }}
+    };
+    assert_eq!(possibly_inverted(&cd1), 1);
+}
+
+#[test]
+fn resistances_that_are_not_numbers_are_skipped() {
+    let cd1 = object! {
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {
+            "PST_FireResistance": {"Curve": [0.2, 0.5, 0.8]},
+            "PST_ColdResistance": 0.5
+        }}}
+    };
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (cd2, report) = cd2ifier::convert(&cd1, &translation_data).unwrap();
+    let grunt = &cd2["EnemiesNoSync"]["ED_Spider_Grunt"];
+    assert!(!grunt["Resistances"].has_key("FireDamageMultiplier"));
+    assert_eq!(grunt["Resistances"]["ColdDamageMultiplier"], 0.5);
+    assert_eq!(report.unsupported_pawn_stats.len(), 1);
+    assert_eq!(report.unsupported_pawn_stats[0].field, "PST_FireResistance");
+}
+
+#[test]
+fn resistances_outside_of_0_to_1_are_clamped_or_kept_with_a_warning() {
+    let cd1 = object! {
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {
+            "PST_FireResistance": 1.5,
+            "PST_ColdResistance": -0.5,
+            "PST_ExplosionResistance": 0.25
+        }}}
+    };
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (cd2, report) = cd2ifier::convert(&cd1, &translation_data).unwrap();
+    let resistances = &cd2["EnemiesNoSync"]["ED_Spider_Grunt"]["Resistances"];
+    // Clamped to immune rather than a negative multiplier:
+    assert_eq!(resistances["FireDamageMultiplier"], 0.0);
+    // A weakness:
+    assert_eq!(resistances["ColdDamageMultiplier"], 1.5);
+    assert_eq!(resistances["ExplosionDamageMultiplier"], 0.75);
+    let out_of_range: Vec<(&str, &str)> = report
+        .out_of_range_resistances
+        .iter()
+        .map(|resistance| (resistance.enemy.as_str(), resistance.field.as_str()))
+        .collect();
+    assert_eq!(
+        out_of_range,
+        [
+            ("ED_Spider_Grunt", "PST_FireResistance"),
+            ("ED_Spider_Grunt", "PST_ColdResistance")
+        ]
+    );
+}
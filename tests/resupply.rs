@@ -0,0 +1,102 @@
+//! The nitra mutator built from StartingNitra and ResupplyCost, for several nitra to cost ratios.
+
+use json::{object, JsonValue};
+
+fn resupply_cost(starting_nitra: f64, resupply_cost: f64) -> JsonValue {
+    let cd1 = object! {
+        "Name": "Resupply",
+        "Description": "Resupply",
+        "StartingNitra": starting_nitra,
+        "ResupplyCost": resupply_cost
+    };
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (cd2, _) = cd2ifier::convert(&cd1, &translation_data).unwrap();
+    cd2["Resupply"]["Cost"].clone()
+}
+
+fn mutator(values: &[f64]) -> JsonValue {
+    object! {"Mutate": "ByResuppliesCalled", "Values": values.to_vec()}
+}
+
+#[test]
+fn less_nitra_than_the_cost_lowers_the_first_resupply() {
+    assert_eq!(resupply_cost(30.0, 80.0), mutator(&[50.0, 80.0]));
+}
+
+#[test]
+fn nitra_equal_to_the_cost_pays_for_the_first_resupply() {
+    assert_eq!(resupply_cost(80.0, 80.0), mutator(&[0.0, 80.0]));
+}
+
+#[test]
+fn nitra_multiple_of_the_cost_has_no_partial_resupply() {
+    assert_eq!(resupply_cost(160.0, 80.0), mutator(&[0.0, 0.0, 80.0]));
+    assert_eq!(resupply_cost(240.0, 80.0), mutator(&[0.0, 0.0, 0.0, 80.0]));
+}
+
+#[test]
+fn leftover_nitra_lowers_the_next_resupply() {
+    assert_eq!(resupply_cost(100.0, 60.0), mutator(&[0.0, 20.0, 60.0]));
+    assert_eq!(
+        resupply_cost(250.0, 100.0),
+        mutator(&[0.0, 0.0, 50.0, 100.0])
+    );
+}
+
+#[test]
+fn no_starting_nitra_keeps_a_flat_cost() {
+    assert_eq!(resupply_cost(0.0, 80.0), JsonValue::from(80.0));
+}
+
+fn convert_resupply(cd1: JsonValue) -> (JsonValue, cd2ifier::ConversionReport) {
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (cd2, report) = cd2ifier::convert(&cd1, &translation_data).unwrap();
+    (cd2["Resupply"]["Cost"].clone(), report)
+}
+
+#[test]
+fn starting_nitra_given_as_a_numeric_string_is_used_with_a_warning() {
+    let (cost, report) = convert_resupply(object! {"StartingNitra": "120", "ResupplyCost": 80});
+    assert_eq!(cost, mutator(&[0.0, 40.0, 80.0]));
+    assert_eq!(report.resupply_warnings.len(), 1);
+}
+
+#[test]
+fn non_numeric_starting_nitra_counts_as_no_starting_nitra() {
+    let (cost, report) = convert_resupply(object! {"StartingNitra": "abc", "ResupplyCost": 80});
+    assert_eq!(cost, 80);
+    assert_eq!(report.resupply_warnings.len(), 1);
+}
+
+#[test]
+fn resupply_cost_given_as_a_numeric_string_is_used_with_a_warning() {
+    let (cost, report) = convert_resupply(object! {"ResupplyCost": " 60 "});
+    assert_eq!(cost, 60);
+    assert_eq!(report.resupply_warnings.len(), 1);
+}
+
+#[test]
+fn huge_starting_nitra_is_capped_with_a_warning() {
+    let (cost, report) = convert_resupply(object! {"StartingNitra": 1e9, "ResupplyCost": 1});
+    let values = &cost["Values"];
+    assert_eq!(values.len(), 101);
+    assert_eq!(values[0], 0.0);
+    assert_eq!(values[100], 1.0);
+    assert_eq!(report.resupply_warnings.len(), 1);
+}
+
+#[test]
+fn zero_resupply_cost_with_starting_nitra_makes_the_capped_resupplies_free() {
+    let (cost, report) = convert_resupply(object! {"StartingNitra": 120, "ResupplyCost": 0});
+    let values: Vec<f64> = cost["Values"]
+        .members()
+        .filter_map(JsonValue::as_f64)
+        .collect();
+    // The free resupplies paid for by the nitra, capped at 100, then the cost itself:
+    assert_eq!(values, vec![0.0; 101]);
+    assert_eq!(cost["Values"].len(), 101);
+    assert_eq!(report.resupply_warnings.len(), 1);
+
+    let (cost, _) = convert_resupply(object! {"ResupplyCost": 0});
+    assert_eq!(cost, 0);
+}
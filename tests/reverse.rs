@@ -0,0 +1,71 @@
+//! `--reverse` converts CD2 files back to CD1, undoing what the forward conversion does and
+//! reporting what has no CD1 equivalent.
+
+use cd2ifier::{default_translation_data, reverse_convert};
+use json::{object, JsonValue};
+
+fn round_trip(cd1: &JsonValue) -> (JsonValue, cd2ifier::ConversionReport) {
+    let translation_data = default_translation_data().unwrap();
+    let (cd2, _) = cd2ifier::convert(cd1, &translation_data).unwrap();
+    reverse_convert(&cd2, &translation_data).unwrap()
+}
+
+#[test]
+fn a_cd1_file_survives_a_round_trip() {
+    let cd1 = object! {
+        "Name": "Round trip",
+        "Description": "Round trip",
+        "StartingNitra": 100,
+        "ResupplyCost": 60,
+        "MaxActiveEnemies": 60,
+        "StationaryEnemies": ["ED_Spider_Stationary_Lobber"],
+        "EnemyDescriptors": {
+            "ED_Spider_Grunt": {
+                "Base": "ED_Spider_Grunt",
+                "PawnStats": {"PST_FireResistance": 0.3, "PST_DamageResistance": 0.5}
+            }
+        }
+    };
+    let (back, report) = round_trip(&cd1);
+    // The nitra mutator collapses back into the two fields:
+    assert_eq!(back["StartingNitra"], 100);
+    assert_eq!(back["ResupplyCost"], 60);
+    // The renamed pool gets its CD1 name back:
+    assert_eq!(back["StationaryEnemies"], cd1["StationaryEnemies"]);
+    assert!(!back.has_key("StationaryPool"));
+    // Resistances are inverted back, except PST_DamageResistance which never was:
+    assert_eq!(
+        back["EnemyDescriptors"]["ED_Spider_Grunt"]["PawnStats"],
+        cd1["EnemyDescriptors"]["ED_Spider_Grunt"]["PawnStats"]
+    );
+    // The default BaseHazard added by the forward conversion is left out:
+    assert!(!back.has_key("BaseHazard"));
+    assert_eq!(back["MaxActiveEnemies"], 60);
+    assert!(report.unsupported_fields.is_empty(), "{report}");
+}
+
+#[test]
+fn cd2_constructs_without_a_cd1_equivalent_are_reported() {
+    let cd2 = object! {
+        "Name": "CD2 only",
+        "Resupply": {"Cost": {"Mutate": "ByPlayerCount", "Values": [80, 60]}, "Extra": 1},
+        "Caps": {"MaxActiveEnemies": 60, "NotACap": 1},
+        "Vars": {"Anything": 1},
+        "EnemiesNoSync": {"ED_Spider_Grunt": {"Base": "ED_Spider_Grunt", "NotAControl": 1}}
+    };
+    let (back, report) = reverse_convert(&cd2, &default_translation_data().unwrap()).unwrap();
+    assert_eq!(back["MaxActiveEnemies"], 60);
+    assert!(!back.has_key("Vars"));
+    for field in ["Resupply.Cost", "Resupply.Extra", "Caps.NotACap", "Vars"] {
+        assert!(
+            report.unsupported_fields.iter().any(|f| f == field),
+            "{field}: {:?}",
+            report.unsupported_fields
+        );
+    }
+    assert!(report
+        .unsupported_fields
+        .iter()
+        .any(|f| f == "EnemiesNoSync.ED_Spider_Grunt.NotAControl"));
+    assert!(!back["EnemyDescriptors"]["ED_Spider_Grunt"].has_key("NotAControl"));
+}
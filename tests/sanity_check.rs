@@ -0,0 +1,54 @@
+//! Bounds of the numbers of converted files, checked with `sanity_check`.
+
+use cd2ifier::{convert_with, default_translation_data, Options};
+use json::object;
+
+fn out_of_bounds_values(cd1: &json::JsonValue) -> Vec<String> {
+    let options = Options {
+        sanity_check: true,
+        ..Options::default()
+    };
+    let (_, report) = convert_with(cd1, &default_translation_data().unwrap(), &options).unwrap();
+    report.out_of_bounds_values
+}
+
+#[test]
+fn values_in_range_pass() {
+    let cd1 = object! {
+        "Name": "In range",
+        "MaxActiveEnemies": 60,
+        "ReviveHealthRatio": [0.2, 0.3, 0.4, 0.5],
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"MinSpawnCount": 2, "MaxSpawnCount": 4}}
+    };
+    assert_eq!(out_of_bounds_values(&cd1), Vec::<String>::new());
+}
+
+#[test]
+fn values_out_of_range_are_reported() {
+    let cd1 = object! {
+        "Name": "Out of range",
+        "MaxActiveEnemies": 0,
+        "ReviveHealthRatio": [0.5, 1.5],
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"MaxSpawnCount": -1}}
+    };
+    assert_eq!(
+        out_of_bounds_values(&cd1),
+        vec![
+            "[Caps.MaxActiveEnemies] is 0, below the minimum of 1",
+            "[DifficultySetting.ReviveHealthRatio] is 1.5, above the maximum of 1",
+            "[EnemiesNoSync.ED_Spider_Grunt.MaxSpawnCount] is -1, below the minimum of 0",
+        ]
+    );
+}
+
+#[test]
+fn bounds_are_not_checked_by_default() {
+    let cd1 = object! {"Name": "Unchecked", "MaxActiveEnemies": 0};
+    let (_, report) = convert_with(
+        &cd1,
+        &default_translation_data().unwrap(),
+        &Options::default(),
+    )
+    .unwrap();
+    assert!(report.out_of_bounds_values.is_empty());
+}
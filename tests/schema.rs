@@ -0,0 +1,42 @@
+//! The check of converted files against the shape CD2 expects.
+
+use cd2ifier::{convert_with, default_translation_data, Options};
+use json::object;
+
+fn schema_violations(cd1: &json::JsonValue) -> Vec<String> {
+    let options = Options {
+        check_schema: true,
+        ..Default::default()
+    };
+    let (_, report) = convert_with(cd1, &default_translation_data().unwrap(), &options).unwrap();
+    report.schema_violations
+}
+
+#[test]
+fn a_proper_conversion_has_no_violations() {
+    let cd1 = object! {
+        "Name": "Schema",
+        "Description": "Schema",
+        "BaseHazard": 4,
+        "MaxActiveEnemies": [60, 70, 80, 90],
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"Base": "ED_Spider_Grunt"}}
+    };
+    assert_eq!(schema_violations(&cd1), Vec::<String>::new());
+}
+
+#[test]
+fn wrong_types_and_hazards_are_violations() {
+    let cd1 = object! {
+        "Name": "Schema",
+        "Description": "Schema",
+        "BaseHazard": 7,
+        "MaxActiveEnemies": "many"
+    };
+    assert_eq!(
+        schema_violations(&cd1),
+        vec![
+            "[DifficultySetting.BaseHazard] must be a hazard from \"Hazard 1\" to \"Hazard 5\", not [Hazard 7]",
+            "[Caps.MaxActiveEnemies] must be a number, not [many]",
+        ]
+    );
+}
@@ -0,0 +1,59 @@
+//! Sections of a CD1 file left out of the conversion with `Options::skip_sections`.
+
+use cd2ifier::{convert_with, default_translation_data, Options, Section};
+use json::object;
+
+fn convert_skipping(skip_sections: Vec<Section>) -> json::JsonValue {
+    let cd1 = object! {
+        "Name": "Sections",
+        "Description": "Sections",
+        "StartingNitra": 0,
+        "ResupplyCost": 60,
+        "MaxActiveEnemies": 60,
+        "HazardBonus": 1,
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_FireResistance": 0.25}}},
+        "EscortMule": {"HealthMultiplier": 2}
+    };
+    let options = Options {
+        skip_sections,
+        ..Default::default()
+    };
+    convert_with(&cd1, &default_translation_data().unwrap(), &options)
+        .unwrap()
+        .0
+}
+
+#[test]
+fn skipped_enemies_are_copied_as_they_are() {
+    let cd2 = convert_skipping(vec![Section::Enemies]);
+    assert_eq!(
+        cd2["EnemyDescriptors"],
+        object! {"ED_Spider_Grunt": {"PawnStats": {"PST_FireResistance": 0.25}}}
+    );
+    assert!(!cd2.has_key("EnemiesNoSync"));
+    assert_eq!(cd2["Caps"]["MaxActiveEnemies"], 60);
+    assert_eq!(cd2["Resupply"]["Cost"], 60);
+    assert_eq!(cd2["EscortMule"]["HealthMultiplier"], 2);
+}
+
+#[test]
+fn only_the_enemies_are_converted_when_the_rest_is_skipped() {
+    let cd2 = convert_skipping(vec![Section::Resupply, Section::Top, Section::Escort]);
+    assert_eq!(
+        cd2["EnemiesNoSync"]["ED_Spider_Grunt"]["Resistances"]["FireDamageMultiplier"],
+        0.75
+    );
+    assert_eq!(cd2["ResupplyCost"], 60);
+    assert_eq!(cd2["MaxActiveEnemies"], 60);
+    // Deprecated fields are only dropped when their section is converted:
+    assert_eq!(cd2["HazardBonus"], 1);
+    assert_eq!(cd2["EscortMule"], object! {"HealthMultiplier": 2});
+    assert!(!cd2.has_key("DifficultySetting"));
+}
+
+#[test]
+fn sections_are_parsed_from_their_names() {
+    assert_eq!("enemies".parse::<Section>(), Ok(Section::Enemies));
+    assert_eq!("Escort".parse::<Section>(), Ok(Section::Escort));
+    assert!("pools".parse::<Section>().is_err());
+}
@@ -0,0 +1,49 @@
+//! `--split`, which writes each top module of a converted file to its own file, and its round
+//! trip through `--resolve-includes`.
+
+use std::fs;
+use std::process::Command;
+
+const CD1: &str = "{\n\"Name\": \"Split\",\n\"Description\": \"First line\nSecond line\",\n\"BaseHazard\": 4,\n\"EnemyPool\": [\"Grunt\"],\n\"EnemyDescriptors\": {\"Grunt\": {\"Base\": \"ED_Grunt\"}}\n}\n";
+
+#[test]
+fn split_files_merge_back_into_the_converted_file() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-split-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.json"), CD1).unwrap();
+    let cd2ifier = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .current_dir(&dir)
+            .args(args)
+            .args(["-q"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    cd2ifier(&["level.json", "--split", "--output-dir", "split"]);
+    // The index keeps the multiline Description as it is:
+    let index = fs::read_to_string(dir.join("split/level.cd2.json")).unwrap();
+    assert!(index.contains("\"First line\nSecond line\""), "{index}");
+    assert!(index.contains("\"level.cd2/Pools.json\""), "{index}");
+    let pools = fs::read_to_string(dir.join("split/level.cd2/Pools.json")).unwrap();
+    assert_eq!(
+        json::parse(&pools).unwrap(),
+        json::object! {"Pools": {"EnemyPool": ["Grunt"]}}
+    );
+    for module in ["DifficultySetting", "Resupply", "EnemiesNoSync"] {
+        assert!(dir.join(format!("split/level.cd2/{module}.json")).is_file());
+    }
+
+    let merged = cd2ifier(&[
+        "split/level.cd2.json",
+        "--resolve-includes",
+        "--reformat",
+        "-o",
+        "-",
+    ]);
+    assert_eq!(merged, cd2ifier(&["level.json", "-o", "-"]));
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,35 @@
+//! The stages of the conversion run on their own.
+
+use cd2ifier::{convert_enemies, convert_resupply, default_translation_data, ConversionReport};
+use json::object;
+
+#[test]
+fn the_enemies_stage_only_builds_the_enemies() {
+    let cd1 = object! {
+        "MaxActiveEnemies": 60,
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_FireResistance": 0.8, "PST_Foo": 1}}}
+    };
+    let mut report = ConversionReport::default();
+    let cd2 = convert_enemies(&cd1, &default_translation_data().unwrap(), &mut report);
+    assert_eq!(cd2.len(), 1);
+    let multiplier =
+        &cd2["EnemiesNoSync"]["ED_Spider_Grunt"]["Resistances"]["FireDamageMultiplier"];
+    assert!((multiplier.as_f64().unwrap() - 0.2).abs() < 1e-9);
+    assert_eq!(report.unsupported_pawn_stats.len(), 1);
+}
+
+#[test]
+fn stages_add_to_the_same_report() {
+    let cd1 = object! {
+        "StartingNitra": "abc",
+        "ResupplyCost": 80,
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_Foo": 1}}}
+    };
+    let mut report = ConversionReport::default();
+    let resupply = convert_resupply(&cd1, &mut report);
+    let enemies = convert_enemies(&cd1, &default_translation_data().unwrap(), &mut report);
+    assert_eq!(resupply, object! {"Resupply": {"Cost": 80.0}});
+    assert!(enemies.has_key("EnemiesNoSync"));
+    assert_eq!(report.resupply_warnings.len(), 1);
+    assert_eq!(report.unsupported_pawn_stats.len(), 1);
+}
@@ -0,0 +1,85 @@
+//! The `_cd2ifier` stamp telling which converter produced a file, added with `Options::stamp` and
+//! by the command line unless `--no-stamp` is given.
+
+use cd2ifier::{Options, STAMP_KEY};
+use json::JsonValue;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const CD1: &str = r#"{"Name": "Stamp", "Description": "Stamp", "MaxActiveEnemies": 60}"#;
+
+fn convert(translation_data: &JsonValue, stamp: bool) -> JsonValue {
+    let options = Options {
+        stamp,
+        check_schema: true,
+        ..Default::default()
+    };
+    let (output, report) = cd2ifier::convert_str_with(CD1, translation_data, &options).unwrap();
+    assert!(report.schema_violations.is_empty(), "{report}");
+    json::parse(&output).unwrap()
+}
+
+fn run_cli(args: &[&str]) -> JsonValue {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+        .arg("-")
+        .args(args)
+        .arg("-q")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(CD1.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    json::parse(&String::from_utf8(output.stdout).unwrap()).unwrap()
+}
+
+#[test]
+fn the_stamp_holds_the_versions_and_comes_last() {
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let converted = convert(&translation_data, true);
+    let (last_key, stamp) = converted.entries().next_back().unwrap();
+    assert_eq!(last_key, STAMP_KEY);
+    assert_eq!(stamp["version"], env!("CARGO_PKG_VERSION"));
+    let hash = stamp["translation_data"].as_str().unwrap();
+    assert_eq!(hash.len(), 16);
+    assert!(hash.chars().all(|c| c.is_ascii_hexdigit()), "{hash}");
+
+    assert!(!convert(&translation_data, false).has_key(STAMP_KEY));
+}
+
+#[test]
+fn the_stamp_tells_the_translation_data_apart() {
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let mut custom = translation_data.clone();
+    custom["SANITY_BOUNDS"]["Caps.MaxActiveEnemies"] = json::object! {"min": 0, "max": 500};
+    let stamp = &convert(&translation_data, true)[STAMP_KEY];
+    assert_eq!(*stamp, convert(&translation_data, true)[STAMP_KEY]);
+    assert_ne!(
+        stamp["translation_data"],
+        convert(&custom, true)[STAMP_KEY]["translation_data"]
+    );
+}
+
+#[test]
+fn the_command_line_stamps_unless_told_not_to() {
+    assert!(run_cli(&[]).has_key(STAMP_KEY));
+    assert!(!run_cli(&["--no-stamp"]).has_key(STAMP_KEY));
+}
+
+#[test]
+fn the_stamp_is_not_converted_back() {
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let converted = convert(&translation_data, true).pretty(4);
+    let (output, report) =
+        cd2ifier::reverse_convert_str_with(&converted, &translation_data, &Options::default())
+            .unwrap();
+    assert!(!output.contains(STAMP_KEY), "{output}");
+    assert!(report.unsupported_fields.is_empty(), "{report}");
+}
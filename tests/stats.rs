@@ -0,0 +1,37 @@
+//! `--stats` logs the time and the counts of what was translated in each file.
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn stats_are_logged_unless_quiet() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-stats-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let level = dir.join("level.json");
+    fs::write(
+        &level,
+        r#"{"Name": "Stats", "Description": "Stats", "MaxActiveEnemies": 60, "MaxActiveSwarmers": 40,
+            "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_FireResistance": 0.5}}}}"#,
+    )
+    .unwrap();
+
+    let run = |quiet: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_cd2ifier"));
+        command.arg(&level).args(["--stats", "--dry-run"]);
+        if quiet {
+            command.arg("-q");
+        }
+        let output = command.output().unwrap();
+        assert!(output.status.success(), "{output:?}");
+        String::from_utf8(output.stderr).unwrap()
+    };
+    let stderr = run(false);
+    assert!(
+        stderr.contains("1 enemies, 2 fields moved, 1 pawn stats remapped in"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("Stats: 1 files in"), "{stderr}");
+    assert!(!run(true).contains("Stats:"));
+    fs::remove_dir_all(dir).unwrap();
+}
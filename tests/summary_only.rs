@@ -0,0 +1,45 @@
+//! `--summary-only`, which prints the numbers of clean, warned and failing files of a batch.
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn summary_only_counts_clean_warned_and_failing_files() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-summary-only-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let clean = r#"{"Name": "Clean", "Description": "Clean", "MaxActiveEnemies": 60}"#;
+    fs::write(dir.join("clean.json"), clean).unwrap();
+    fs::write(dir.join("other_clean.json"), clean).unwrap();
+    let warned = r#"{"Name": "Warned", "Description": "Warned", "UnknownField": 1}"#;
+    fs::write(dir.join("warned.json"), warned).unwrap();
+    fs::write(dir.join("failing.json"), r#"{"Name": "#).unwrap();
+
+    let summary_only = || {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .arg(&dir)
+            .arg("--summary-only")
+            .output()
+            .unwrap()
+    };
+    let output = summary_only();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "2 clean, 1 with warnings, 1 failing\n"
+    );
+    // Nothing is logged about the files themselves, nor written:
+    assert!(!String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("warned.json"));
+    assert!(!dir.join("clean.cd2.json").exists());
+
+    fs::remove_file(dir.join("failing.json")).unwrap();
+    let output = summary_only();
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "2 clean, 1 with warnings, 0 failing\n"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
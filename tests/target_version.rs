@@ -0,0 +1,86 @@
+//! Translation rules gated by the CD2 version the converted file is meant for, and by the one
+//! it was already migrated to.
+
+use cd2ifier::{convert_with, default_translation_data, Options};
+use json::{array, object, JsonValue};
+
+fn convert_for(target_version: Option<&str>) -> (JsonValue, cd2ifier::ConversionReport) {
+    let mut translation_data = default_translation_data().unwrap();
+    translation_data["BASE_HAZARD_DEFAULT"]["version"] = "1.1".into();
+    translation_data["POOL_RENAMES"]["StationaryEnemies"]["max_version"] = "1.1".into();
+    translation_data["PAWN_STATS"]["PST_MovementSpeed"] = array![
+        {"version": "1.0", "max_version": "1.1", "CD2_module": "Movement", "CD2_field": "SpeedMultiplier"}
+    ];
+    let cd1 = object! {
+        "StationaryEnemies": [],
+        "EnemyDescriptors": {"ED_Spider_Grunt": {"PawnStats": {"PST_MovementSpeed": 1.5}}}
+    };
+    let options = Options {
+        target_version: target_version.map(str::to_string),
+        ..Default::default()
+    };
+    convert_with(&cd1, &translation_data, &options).unwrap()
+}
+
+#[test]
+fn rules_introduced_after_the_target_are_not_applied() {
+    let (cd2, _) = convert_for(Some("1.0"));
+    assert!(!cd2.has_key("DifficultySetting"));
+    let (cd2, _) = convert_for(Some("1.1"));
+    assert_eq!(cd2["DifficultySetting"]["BaseHazard"], "Hazard 5");
+}
+
+#[test]
+fn rules_replaced_before_the_target_are_not_applied() {
+    let (cd2, _) = convert_for(Some("1.1"));
+    assert!(cd2["Pools"].has_key("StationaryPool"));
+    assert_eq!(
+        cd2["EnemiesNoSync"]["ED_Spider_Grunt"]["Movement"]["SpeedMultiplier"],
+        1.5
+    );
+
+    let (cd2, report) = convert_for(Some("1.2"));
+    assert!(cd2["Pools"].has_key("StationaryEnemies"));
+    assert!(!cd2["EnemiesNoSync"]["ED_Spider_Grunt"].has_key("Movement"));
+    assert_eq!(report.unsupported_pawn_stats.len(), 1);
+}
+
+#[test]
+fn the_latest_version_skips_every_replaced_rule() {
+    let (cd2, _) = convert_for(None);
+    assert_eq!(cd2["DifficultySetting"]["BaseHazard"], "Hazard 5");
+    assert!(cd2["Pools"].has_key("StationaryEnemies"));
+}
+
+fn convert_since(version: &str, since_version: Option<&str>) -> JsonValue {
+    let cd1 = object! {"Version": version, "StationaryEnemies": ["ED_Spider_Stationary_Lobber"]};
+    let options = Options {
+        since_version: since_version.map(str::to_string),
+        ..Default::default()
+    };
+    convert_with(&cd1, &default_translation_data().unwrap(), &options)
+        .unwrap()
+        .0
+}
+
+#[test]
+fn a_cd2_version_field_skips_the_rules_up_to_it() {
+    let cd2 = convert_since("1.0", None);
+    assert!(!cd2.has_key("DifficultySetting"));
+    assert!(cd2["Pools"].has_key("StationaryEnemies"));
+}
+
+#[test]
+fn a_version_field_unknown_to_the_translation_data_is_ignored() {
+    for version in ["3", "2.7.1", "beta"] {
+        let cd2 = convert_since(version, None);
+        assert_eq!(
+            cd2["DifficultySetting"]["BaseHazard"], "Hazard 5",
+            "{version}"
+        );
+        assert!(cd2["Pools"].has_key("StationaryPool"), "{version}");
+    }
+    // Unless it is given explicitly:
+    let cd2 = convert_since("beta", Some("3"));
+    assert!(!cd2.has_key("DifficultySetting"));
+}
@@ -0,0 +1,51 @@
+//! Enemy bases missing from the `KNOWN_BASE_ENEMIES` of the translation data, most likely
+//! mistyped, which only fail the conversion in strict mode.
+
+use std::fs;
+use std::process::Command;
+
+const CD1: &str = r#"{"Name": "Bases", "Description": "Bases", "EnemyDescriptors": {
+    "ED_Spider_Grunt": {"Base": "ED_Spider_Grunt"},
+    "ED_Custom": {"Base": "ED_Spider"}
+}}"#;
+
+#[test]
+fn unknown_bases_are_reported_with_their_enemy() {
+    let translation_data = cd2ifier::default_translation_data().unwrap();
+    let (_, report) =
+        cd2ifier::convert_str_with(CD1, &translation_data, &Default::default()).unwrap();
+    assert_eq!(report.unknown_bases.len(), 1);
+    assert_eq!(report.unknown_bases[0].enemy, "ED_Custom");
+    assert_eq!(report.unknown_bases[0].base, "ED_Spider");
+}
+
+#[test]
+fn unknown_bases_only_fail_strict_mode() {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-bases-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("level.json"), CD1).unwrap();
+    let cd2ifier = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .current_dir(&dir)
+            .arg("level.json")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = cd2ifier(&["--strict"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Unknown base enemy [ED_Spider] in [ED_Custom]"));
+    assert!(!dir.join("level.cd2.json").exists());
+
+    let output = cd2ifier(&[]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Unknown base enemy: [ED_Spider] in [ED_Custom]. Is it mistyped?"));
+    assert!(dir.join("level.cd2.json").is_file());
+    fs::remove_dir_all(dir).unwrap();
+}
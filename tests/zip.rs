@@ -0,0 +1,127 @@
+//! Zip archives of CD1 files, converted into a new archive or into separate files.
+
+// The archives are built with the zip crate, a dependency of the command line tool only:
+#![cfg(feature = "cli")]
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+/// Builds an archive of stored entries.
+fn zip(entries: &[(&str, &str)]) -> Vec<u8> {
+    let mut archive = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for (name, content) in entries {
+        archive.start_file(*name, options).unwrap();
+        archive.write_all(content.as_bytes()).unwrap();
+    }
+    archive.finish().unwrap().into_inner()
+}
+
+const CD1: &str = r#"{"Name": "Zip", "Description": "Zip", "MaxActiveEnemies": 60}"#;
+
+fn run(dir: &Path, args: &[&str]) {
+    let output = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+        .args(args)
+        .arg("-q")
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("cd2ifier-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn the_files_of_an_archive_are_converted_into_the_output_directory() {
+    let dir = temp_dir("zip-output-dir");
+    fs::write(
+        dir.join("pack.zip"),
+        zip(&[("levels/a.json", CD1), ("README.txt", "Hello")]),
+    )
+    .unwrap();
+
+    run(&dir, &["pack.zip", "--output-dir", "out", "--no-stamp"]);
+    let converted = fs::read_to_string(dir.join("out/levels/a.cd2.json")).unwrap();
+    assert_eq!(converted, cd2ifier::convert_str(CD1).unwrap());
+    assert!(!dir.join("out/README.txt").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn an_archive_is_converted_into_a_new_archive() {
+    let dir = temp_dir("zip-archive");
+    fs::write(
+        dir.join("pack.zip"),
+        zip(&[("levels/a.json", CD1), ("README.txt", "Hello")]),
+    )
+    .unwrap();
+
+    run(&dir, &["pack.zip", "--no-stamp"]);
+    // The converted archive is read back by reformatting its files into a directory:
+    run(
+        &dir,
+        &[
+            "pack.cd2.zip",
+            "--reformat",
+            "--output-dir",
+            "out",
+            "--output-suffix",
+            ".x",
+        ],
+    );
+    let converted = fs::read_to_string(dir.join("out/levels/a.cd2.x.json")).unwrap();
+    assert_eq!(converted, cd2ifier::convert_str(CD1).unwrap());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn archives_with_data_descriptors_and_zip64_entries_are_read() {
+    let dir = temp_dir("zip-stream");
+    // Written as a stream, the sizes and CRC of each entry follow it in a data descriptor:
+    let mut archive = ZipWriter::new_stream(Vec::new());
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    archive.start_file("levels/a.json", options).unwrap();
+    archive.write_all(CD1.as_bytes()).unwrap();
+    let archive = archive.finish().unwrap().into_inner();
+    assert_ne!(archive[6] & 0x08, 0, "No data descriptor");
+    fs::write(dir.join("pack.zip"), archive).unwrap();
+
+    run(&dir, &["pack.zip", "--output-dir", "out", "--no-stamp"]);
+    let converted = fs::read_to_string(dir.join("out/levels/a.cd2.json")).unwrap();
+    assert_eq!(converted, cd2ifier::convert_str(CD1).unwrap());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn entries_leading_outside_of_the_archive_are_refused() {
+    let dir = temp_dir("zip-slip");
+    for name in ["../../escaped.json", "/absolute.json"] {
+        fs::write(dir.join("pack.zip"), zip(&[(name, CD1)])).unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_cd2ifier"))
+            .args(["pack.zip", "--output-dir", "out/a", "-q"])
+            .current_dir(&dir)
+            .output()
+            .unwrap();
+        assert!(!output.status.success(), "{output:?}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!(
+                "The entry {name} of the archive points outside of it"
+            )),
+            "{stderr}"
+        );
+    }
+    assert!(!dir.join("escaped.cd2.json").exists());
+    assert!(!dir.join("out").exists());
+    fs::remove_dir_all(dir).unwrap();
+}